@@ -0,0 +1,96 @@
+/*
+SPUStudnet
+12/15/2024
+simulation.rs
+Benchmarks how fast whole games can be simulated.
+Every game is played start to finish by bots, with the log turned off, as a strategy simulation would run them.
+The "thrpt" criterion reports is games per second.
+Also times forking a game, which lookahead does a lot of, and playing games on every core at once.
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use dominion_library::game::game_manager::GameManager;
+use dominion_library::game::kingdom::KingdomChoice;
+use dominion_library::player::seat::Seat;
+use dominion_library::game::game_state::GameState;
+
+/**
+ * play_bot_game
+ * Play a full game between bots from the given seed, returning how many turns it took.
+ */
+fn play_bot_game(seed: u64, seat_count: usize) -> u32 {
+    let seats = (0..seat_count).map(|i| Seat::bot(&format!("Bot {}", i + 1))).collect();
+    let game = GameManager::simulate(seats, Some(seed), KingdomChoice::Random).expect("A game between bots should always set up.");
+    assert!(game.check_ending().is_some(), "A game between bots should play to the end.");
+    return game.get_turn_controller().get_turn_number();
+}
+
+/**
+ * bench_games
+ * Games per second, for two and four player games.
+ */
+fn bench_games(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bot games");
+    group.throughput(Throughput::Elements(1));
+
+    for seat_count in [2, 4] {
+        let mut seed = 0;
+        group.bench_function(format!("{} players", seat_count), |b| b.iter(|| {
+            seed += 1;
+            return play_bot_game(black_box(seed), seat_count);
+        }));
+    }
+
+    group.finish();
+}
+
+/**
+ * bench_fork
+ * How long it takes to fork a game partway through, as search-based bots fork over and over.
+ */
+fn bench_fork(c: &mut Criterion) {
+    // Play a game between a human and a bot for a few turns, so there's something in every deck and discard.
+    let mut game = GameManager::new(vec![Seat::human("Human"), Seat::bot("Bot")], Some(1)).expect("A game should always set up.");
+    for _ in 0..10 {
+        game.end_turn().expect("The game shouldn't be over yet.");
+    }
+
+    let state: &GameState = game.get_state();
+    let mut fork_seed = 0;
+    c.bench_function("fork a game", |b| b.iter(|| {
+        fork_seed += 1;
+        return state.fork(black_box(fork_seed));
+    }));
+}
+
+/**
+ * bench_parallel_games
+ * Games per second with a thread per core, each playing its own games.
+ */
+fn bench_parallel_games(c: &mut Criterion) {
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as u64;
+    let games_per_thread = 16;
+
+    let mut group = c.benchmark_group("bot games in parallel");
+    group.throughput(Throughput::Elements(threads * games_per_thread));
+
+    let mut round = 0;
+    group.bench_function(format!("2 players, {} threads", threads), |b| b.iter(|| {
+        round += 1;
+        std::thread::scope(|scope| {
+            for thread in 0..threads {
+                let first_seed = (round * threads + thread) * games_per_thread;
+                scope.spawn(move || {
+                    for seed in first_seed..first_seed + games_per_thread {
+                        play_bot_game(black_box(seed), 2);
+                    }
+                });
+            }
+        });
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_games, bench_fork, bench_parallel_games);
+criterion_main!(benches);
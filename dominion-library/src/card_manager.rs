@@ -7,5 +7,7 @@ Reference module, required by rust to reference modules in folder structures.
 
 pub mod card;
 pub mod card_builder;
+pub mod card_loader;
+pub mod card_registry;
 pub mod card_structures;
 pub mod card_properties;
\ No newline at end of file
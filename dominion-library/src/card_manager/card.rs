@@ -19,6 +19,7 @@ pub struct CardProperties {
     pub(crate) played_during: PlayerPhases,
     pub(crate) cost: i32,
     pub(crate) card_types: Vec<CardTypes>,
+    pub(crate) expansion: Expansion,
     pub(crate) card_type_properties: TypeProperties
 
 }
@@ -65,6 +66,7 @@ pub trait Card {
     fn get_playing_phase(&self) -> &PlayerPhases;
     fn get_cost(&self) -> i32;
     fn get_card_types(&self) -> &Vec<CardTypes>;
+    fn get_expansion(&self) -> Expansion;
     fn get_relevant_value(&self) -> i32;
     fn get_description(&self) -> &String;
     fn get_name(&self) -> &String;
@@ -88,11 +90,15 @@ impl Card for CardProperties {
         return &self.card_types;
     }
 
+    fn get_expansion(&self) -> Expansion {
+        return self.expansion;
+    }
+
     fn get_relevant_value(&self) -> i32 {
         if self.card_types.contains(&CardTypes::Treasure) {
             return self.card_type_properties.treasure_properties.get_value()
         }
-        else if self.card_types.contains(&CardTypes::Victory) {
+        else if self.card_types.contains(&CardTypes::Victory) || self.card_types.contains(&CardTypes::Curse) {
             self.card_type_properties.victory_properties.get_points()
         }
         else {
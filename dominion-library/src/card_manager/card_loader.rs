@@ -0,0 +1,340 @@
+/*
+SPUStudnet
+12/15/2024
+card_loader.rs
+Loads card definitions from data files (TOML or JSON), and checks them before they go in the card registry.
+*/
+
+use std::fs;
+use std::ops::RangeInclusive;
+
+use serde::Deserialize;
+
+use crate::card_manager::card::CardProperties;
+use crate::card_manager::card_builder::build_card_properties;
+use crate::card_manager::card_properties::{ActionEvents, CardTypes, Expansion};
+use crate::game::game_errors::CardDataError;
+
+/**
+ * CARD_AMOUNTS / CARD_POINTS
+ * The costs and treasure values a card can have (they're counted in u8s once the game is running),
+ * and the victory points it can be worth (negative for curses).
+ */
+const CARD_AMOUNTS: RangeInclusive<i32> = 0..=255;
+const CARD_POINTS: RangeInclusive<i32> = -255..=255;
+
+/**
+ * CardDefinition
+ * One card, as it's written in a data file.
+ * Only the name, cost and types are required; everything else defaults to nothing.
+ */
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CardDefinition {
+    pub name: String,
+    pub cost: i32,
+    pub types: Vec<String>,
+    // The expansion the card comes from (the base set, if it isn't given).
+    #[serde(default)]
+    pub expansion: Option<String>,
+    // Treasures only: buying power.
+    #[serde(default)]
+    pub value: Option<i32>,
+    // Victories and curses only: points.
+    #[serde(default)]
+    pub points: Option<i32>,
+    // Actions only: the + bonuses.
+    #[serde(default)]
+    pub cards: u8,
+    #[serde(default)]
+    pub actions: u8,
+    #[serde(default)]
+    pub buys: u8,
+    #[serde(default)]
+    pub coins: u8,
+    // Actions only: the built-in effect that does the rest of the card.
+    #[serde(default)]
+    pub effect: Option<String>,
+    // Extra lines of card text, for the description.
+    #[serde(default)]
+    pub text: Vec<String>
+}
+
+/**
+ * CardFile
+ * The layout of a card data file: a list of cards under "card".
+ * ([[card]] tables in TOML, or {"card": [...]} in JSON.)
+ */
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardFile {
+    card: Vec<CardDefinition>
+}
+
+/**
+ * load_cards_from_toml
+ * Load and check cards from TOML text. The source names where the text came from, for errors.
+ */
+pub fn load_cards_from_toml(text: &str, source: &str) -> Result<Vec<CardProperties>, CardDataError> {
+    return match toml::from_str::<CardFile>(text) {
+        Ok(file) => build_cards(file.card, source),
+        Err(err) => Err(CardDataError {source: String::from(source), problems: vec![err.to_string()]})
+    };
+}
+
+/**
+ * load_cards_from_json
+ * Load and check cards from JSON text. The source names where the text came from, for errors.
+ */
+pub fn load_cards_from_json(text: &str, source: &str) -> Result<Vec<CardProperties>, CardDataError> {
+    return match serde_json::from_str::<CardFile>(text) {
+        Ok(file) => build_cards(file.card, source),
+        Err(err) => Err(CardDataError {source: String::from(source), problems: vec![err.to_string()]})
+    };
+}
+
+/**
+ * load_cards_from_file
+ * Load and check cards from a .toml or .json file.
+ */
+pub fn load_cards_from_file(path: &str) -> Result<Vec<CardProperties>, CardDataError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            return Err(CardDataError {source: String::from(path), problems: vec![err.to_string()]});
+        }
+    };
+
+    if path.ends_with(".json") {
+        return load_cards_from_json(&text, path);
+    }
+    else if path.ends_with(".toml") {
+        return load_cards_from_toml(&text, path);
+    }
+
+    return Err(CardDataError {source: String::from(path), problems: vec![String::from("Card files have to end in .toml or .json.")]});
+}
+
+/**
+ * build_cards
+ * Check every definition, and build the cards if they're all fine.
+ * Every problem is reported at once, so a file can be fixed in one go.
+ */
+fn build_cards(definitions: Vec<CardDefinition>, source: &str) -> Result<Vec<CardProperties>, CardDataError> {
+    let mut problems = Vec::new();
+    let mut cards = Vec::new();
+
+    for (i, definition) in definitions.iter().enumerate() {
+        if definitions[..i].iter().any(|d| d.name == definition.name) {
+            problems.push(format!("{}: defined more than once.", definition.name));
+            continue;
+        }
+
+        match check_definition(definition) {
+            Ok((card_types, expansion, event)) => cards.push(build_card_properties(definition, card_types, expansion, event)),
+            Err(mut card_problems) => problems.append(&mut card_problems)
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(CardDataError {source: String::from(source), problems: problems});
+    }
+
+    return Ok(cards);
+}
+
+/**
+ * check_definition
+ * Check one card definition: its name, cost, types, expansion, and that each field suits the card's types.
+ * Returns the card's types, expansion and effect if it's fine, or every problem with it if not.
+ */
+fn check_definition(definition: &CardDefinition) -> Result<(Vec<CardTypes>, Expansion, ActionEvents), Vec<String>> {
+    let name = if definition.name.trim().is_empty() { "(unnamed card)" } else { definition.name.as_str() };
+    let mut problems = Vec::new();
+
+    if definition.name.trim().is_empty() {
+        problems.push(format!("{}: needs a name.", name));
+    }
+    if !CARD_AMOUNTS.contains(&definition.cost) {
+        problems.push(format!("{}: costs {}, but costs have to be from 0 to 255.", name, definition.cost));
+    }
+
+    let mut card_types = Vec::new();
+    for type_name in &definition.types {
+        match CardTypes::from_name(type_name) {
+            Some(card_type) => card_types.push(card_type),
+            None => problems.push(format!("{}: {} isn't a card type (use Action, Treasure, Victory, Reaction, Attack or Curse).", name, type_name))
+        }
+    }
+    if definition.types.is_empty() {
+        problems.push(format!("{}: needs at least one type.", name));
+    }
+
+    let mut expansion = Expansion::Base;
+    if let Some(expansion_name) = &definition.expansion {
+        match Expansion::from_name(expansion_name) {
+            Some(e) => expansion = e,
+            None => {
+                let known: Vec<&str> = Expansion::EXPANSION_NAMES.iter().map(|(n, _)| *n).collect();
+                problems.push(format!("{}: {} isn't an expansion (known expansions: {}).", name, expansion_name, known.join(", ")));
+            }
+        }
+    }
+
+    let is_action = card_types.contains(&CardTypes::Action);
+    let is_treasure = card_types.contains(&CardTypes::Treasure);
+    let is_scored = card_types.contains(&CardTypes::Victory) || card_types.contains(&CardTypes::Curse);
+
+    match definition.value {
+        Some(_) if !is_treasure => problems.push(format!("{}: has a value, but isn't a Treasure.", name)),
+        None if is_treasure => problems.push(format!("{}: is a Treasure, but has no value.", name)),
+        _ => {}
+    }
+    if let Some(value) = definition.value {
+        if !CARD_AMOUNTS.contains(&value) {
+            problems.push(format!("{}: is worth {}, but values have to be from 0 to 255.", name, value));
+        }
+    }
+    if definition.points.is_some() && !is_scored {
+        problems.push(format!("{}: has points, but isn't a Victory or Curse.", name));
+    }
+    if let Some(points) = definition.points {
+        if !CARD_POINTS.contains(&points) {
+            problems.push(format!("{}: is worth {} points, but points have to be from -255 to 255.", name, points));
+        }
+    }
+    if !is_action && (definition.cards > 0 || definition.actions > 0 || definition.buys > 0 || definition.coins > 0) {
+        problems.push(format!("{}: has + bonuses, but isn't an Action.", name));
+    }
+
+    let mut event = ActionEvents::No;
+    if let Some(effect) = &definition.effect {
+        if !is_action {
+            problems.push(format!("{}: has an effect, but isn't an Action.", name));
+        }
+        match ActionEvents::from_name(effect) {
+            Some(e) => event = e,
+            None => {
+                let known: Vec<&str> = ActionEvents::EFFECT_NAMES.iter().map(|(n, _)| *n).collect();
+                problems.push(format!("{}: {} isn't an effect (known effects: {}).", name, effect, known.join(", ")));
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    return Ok((card_types, expansion, event));
+}
+
+/**
+ * Loader tests
+ * Card files with problems in them, checked to be turned away with every problem listed.
+ */
+#[cfg(test)]
+mod loader_tests {
+    use super::*;
+
+    // Load the TOML text, expecting it to be turned away, and return the problems found with it.
+    fn problems(text: &str) -> Vec<String> {
+        return match load_cards_from_toml(text, "test.toml") {
+            Ok(_) => panic!("The cards should have been turned away."),
+            Err(error) => error.problems
+        };
+    }
+
+    #[test]
+    fn costs_values_and_points_out_of_range_are_all_listed() {
+        let text = r#"
+            [[card]]
+            name = "Hoard"
+            cost = 300
+            types = ["Treasure"]
+            value = 256
+
+            [[card]]
+            name = "Debt"
+            cost = -1
+            types = ["Treasure"]
+            value = -2
+
+            [[card]]
+            name = "Palace"
+            cost = 5
+            types = ["Victory"]
+            points = 1000
+
+            [[card]]
+            name = "Doom"
+            cost = 0
+            types = ["Curse"]
+            points = -256
+        "#;
+
+        assert_eq!(problems(text), [
+            "Hoard: costs 300, but costs have to be from 0 to 255.",
+            "Hoard: is worth 256, but values have to be from 0 to 255.",
+            "Debt: costs -1, but costs have to be from 0 to 255.",
+            "Debt: is worth -2, but values have to be from 0 to 255.",
+            "Palace: is worth 1000 points, but points have to be from -255 to 255.",
+            "Doom: is worth -256 points, but points have to be from -255 to 255."
+        ]);
+    }
+
+    #[test]
+    fn every_problem_in_a_file_is_listed() {
+        let text = r#"
+            [[card]]
+            name = "Coin"
+            cost = 2
+            types = ["Treasure"]
+
+            [[card]]
+            name = "Shrine"
+            cost = 4
+            types = ["Temple"]
+            cards = 2
+
+            [[card]]
+            name = "Coin"
+            cost = 2
+            types = ["Treasure"]
+            value = 1
+
+            [[card]]
+            name = ""
+            cost = 3
+            types = []
+        "#;
+
+        assert_eq!(problems(text), [
+            "Coin: is a Treasure, but has no value.",
+            "Shrine: Temple isn't a card type (use Action, Treasure, Victory, Reaction, Attack or Curse).",
+            "Shrine: has + bonuses, but isn't an Action.",
+            "Coin: defined more than once.",
+            "(unnamed card): needs a name.",
+            "(unnamed card): needs at least one type."
+        ]);
+    }
+
+    #[test]
+    fn amounts_at_the_limits_load() {
+        let text = r#"
+            [[card]]
+            name = "Hoard"
+            cost = 255
+            types = ["Treasure"]
+            value = 255
+
+            [[card]]
+            name = "Doom"
+            cost = 0
+            types = ["Curse"]
+            points = -255
+        "#;
+
+        let cards = load_cards_from_toml(text, "test.toml").expect("The cards are all in range.");
+        assert_eq!(cards.len(), 2);
+    }
+}
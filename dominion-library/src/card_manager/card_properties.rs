@@ -5,18 +5,122 @@ card_properties.rs
 File containing several struct compositions of card properties.
 */
 
+use serde::{Deserialize, Serialize};
+
 /**
  * CardTypes
  * Enum representing when cards can be played, represented by an integer.
  * Integer representation was critical for a function I didn't get to implement.
  */
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum CardTypes {
     Victory = 0,
     Treasure = 1,
     Action = 2,
     Reaction = 3,
-    Attack = 4
+    Attack = 4,
+    Curse = 5
+}
+
+/**
+ * CardTypes implementation
+ */
+impl CardTypes {
+    /**
+     * from_name
+     * Find a card type by the name card data files use for it ("Action", "Treasure", etc.)
+     */
+    pub fn from_name(name: &str) -> Option<CardTypes> {
+        return match name {
+            "Victory" => Some(CardTypes::Victory),
+            "Treasure" => Some(CardTypes::Treasure),
+            "Action" => Some(CardTypes::Action),
+            "Reaction" => Some(CardTypes::Reaction),
+            "Attack" => Some(CardTypes::Attack),
+            "Curse" => Some(CardTypes::Curse),
+            _ => None
+        };
+    }
+
+    /**
+     * get_name
+     * The name of the card type, as it's written on cards.
+     */
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            CardTypes::Victory => "Victory",
+            CardTypes::Treasure => "Treasure",
+            CardTypes::Action => "Action",
+            CardTypes::Reaction => "Reaction",
+            CardTypes::Attack => "Attack",
+            CardTypes::Curse => "Curse"
+        };
+    }
+}
+
+/**
+ * Expansion
+ * Which box a card comes from.
+ */
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Expansion {
+    #[default]
+    Base,
+    Intrigue,
+    Seaside,
+    Alchemy,
+    Prosperity,
+    Cornucopia,
+    Hinterlands,
+    DarkAges,
+    Guilds,
+    Adventures,
+    Empires,
+    Nocturne,
+    Renaissance,
+    Menagerie,
+    Allies,
+    Plunder,
+    RisingSun,
+    Promo
+}
+
+/**
+ * Expansion implementation
+ */
+impl Expansion {
+    /**
+     * EXPANSION_NAMES
+     * The name card data files use for each expansion, as it's printed on the box.
+     */
+    pub const EXPANSION_NAMES: [(&'static str, Expansion); 18] = [
+        ("Base", Expansion::Base),
+        ("Intrigue", Expansion::Intrigue),
+        ("Seaside", Expansion::Seaside),
+        ("Alchemy", Expansion::Alchemy),
+        ("Prosperity", Expansion::Prosperity),
+        ("Cornucopia", Expansion::Cornucopia),
+        ("Hinterlands", Expansion::Hinterlands),
+        ("Dark Ages", Expansion::DarkAges),
+        ("Guilds", Expansion::Guilds),
+        ("Adventures", Expansion::Adventures),
+        ("Empires", Expansion::Empires),
+        ("Nocturne", Expansion::Nocturne),
+        ("Renaissance", Expansion::Renaissance),
+        ("Menagerie", Expansion::Menagerie),
+        ("Allies", Expansion::Allies),
+        ("Plunder", Expansion::Plunder),
+        ("Rising Sun", Expansion::RisingSun),
+        ("Promo", Expansion::Promo)
+    ];
+
+    /**
+     * from_name
+     * Find an expansion by the name card data files use for it.
+     */
+    pub fn from_name(name: &str) -> Option<Expansion> {
+        return Expansion::EXPANSION_NAMES.iter().find(|(n, _)| *n == name).map(|(_, e)| *e);
+    }
 }
 
 /**
@@ -103,9 +207,65 @@ impl ActionProperties {
  * Different events, signaled to the game, that can happen.
  * These are required as certain cards require additional dialogue.
  */
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum ActionEvents {
     Workshop,
     Merchant,
+    Chapel,
+    Remodel,
+    Mine,
+    Moneylender,
+    Militia,
+    Witch,
+    Bandit,
+    Cellar,
+    Harbinger,
+    Vassal,
+    Bureaucrat,
+    Poacher,
+    ThroneRoom,
+    CouncilRoom,
+    Library,
+    Sentry,
+    Artisan,
     No
-}
\ No newline at end of file
+}
+
+/**
+ * ActionEvents implementation
+ */
+impl ActionEvents {
+    /**
+     * EFFECT_NAMES
+     * The name card data files use for each effect, named after the card that uses it.
+     */
+    pub const EFFECT_NAMES: [(&'static str, ActionEvents); 19] = [
+        ("Workshop", ActionEvents::Workshop),
+        ("Merchant", ActionEvents::Merchant),
+        ("Chapel", ActionEvents::Chapel),
+        ("Remodel", ActionEvents::Remodel),
+        ("Mine", ActionEvents::Mine),
+        ("Moneylender", ActionEvents::Moneylender),
+        ("Militia", ActionEvents::Militia),
+        ("Witch", ActionEvents::Witch),
+        ("Bandit", ActionEvents::Bandit),
+        ("Cellar", ActionEvents::Cellar),
+        ("Harbinger", ActionEvents::Harbinger),
+        ("Vassal", ActionEvents::Vassal),
+        ("Bureaucrat", ActionEvents::Bureaucrat),
+        ("Poacher", ActionEvents::Poacher),
+        ("Throne Room", ActionEvents::ThroneRoom),
+        ("Council Room", ActionEvents::CouncilRoom),
+        ("Library", ActionEvents::Library),
+        ("Sentry", ActionEvents::Sentry),
+        ("Artisan", ActionEvents::Artisan)
+    ];
+
+    /**
+     * from_name
+     * Find an effect by the name card data files use for it.
+     */
+    pub fn from_name(name: &str) -> Option<ActionEvents> {
+        return ActionEvents::EFFECT_NAMES.iter().find(|(n, _)| *n == name).map(|(_, e)| *e);
+    }
+}
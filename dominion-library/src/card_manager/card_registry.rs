@@ -0,0 +1,319 @@
+/*
+SPUStudnet
+12/15/2024
+card_registry.rs
+The card registry: every card the game knows about, each given a small CardId.
+Piles, hands, decks and the trash hold CardIds, and look up the card's details here without building anything.
+*/
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::card_manager::card::{Card, CardProperties};
+use crate::card_manager::card_loader::{load_cards_from_file, load_cards_from_toml};
+use crate::card_manager::card_properties::{ActionProperties, CardTypes, Expansion};
+use crate::game::game_errors::CardDataError;
+use crate::game::pile_builder::BASIC_SUPPLY;
+use crate::player::phases::PlayerPhases;
+
+/**
+ * BASE_SET_CARDS
+ * The built-in card definitions, used unless another file is loaded first.
+ */
+const BASE_SET_CARDS: &str = include_str!("../../cards/base_set.toml");
+
+/**
+ * CARD_REGISTRY
+ * The cards every game is played with. Set once, the first time cards are needed or loaded.
+ */
+static CARD_REGISTRY: OnceLock<CardRegistry> = OnceLock::new();
+
+/**
+ * CardId
+ * A card's identity: its place in the card registry.
+ * Two copies of the same card always have the same id, and ids stay the same for as long as the program runs.
+ * The id itself is a card (see the Card implementation below), so it can be used anywhere a card's details are needed.
+ */
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct CardId(pub(crate) u16);
+
+/**
+ * CardIds are saved as the card's name rather than the number, as the numbers depend on the order the cards were loaded in.
+ */
+impl Serialize for CardId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(self.get_name());
+    }
+}
+
+impl<'de> Deserialize<'de> for CardId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CardId, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        return CardId::named(&name).ok_or_else(|| serde::de::Error::custom(format!("there is no card named {}", name)));
+    }
+}
+
+/**
+ * CardId implementation
+ */
+impl CardId {
+    /**
+     * named
+     * Find the id of a card by name.
+     * Returns nothing if there's no card with that name.
+     */
+    pub fn named(card_name: &str) -> Option<CardId> {
+        return card_registry().find(card_name);
+    }
+
+    /**
+     * get_index
+     * The id as a number, counting from 0 in the order the cards were loaded.
+     */
+    pub fn get_index(&self) -> u16 {
+        return self.0;
+    }
+
+    /**
+     * get_properties
+     * The card's details, from the registry.
+     */
+    pub fn get_properties(&self) -> &'static CardProperties {
+        return card_registry().get(*self);
+    }
+}
+
+/**
+ * Implementing the card getters for CardId, by looking the card up in the registry.
+ * None of these build or copy a card (besides get_action_properties, which is a small copy).
+ */
+impl Card for CardId {
+    fn get_playing_phase(&self) -> &PlayerPhases {
+        return self.get_properties().get_playing_phase();
+    }
+
+    fn get_cost(&self) -> i32 {
+        return self.get_properties().get_cost();
+    }
+
+    fn get_card_types(&self) -> &Vec<CardTypes> {
+        return self.get_properties().get_card_types();
+    }
+
+    fn get_expansion(&self) -> Expansion {
+        return self.get_properties().get_expansion();
+    }
+
+    fn get_relevant_value(&self) -> i32 {
+        return self.get_properties().get_relevant_value();
+    }
+
+    fn get_description(&self) -> &String {
+        return self.get_properties().get_description();
+    }
+
+    fn get_name(&self) -> &String {
+        return self.get_properties().get_name();
+    }
+
+    fn get_action_properties(&self) -> ActionProperties {
+        return self.get_properties().get_action_properties();
+    }
+}
+
+/**
+ * KnownCards
+ * The ids of the cards the game's own rules mention: the basic supply it builds, the cards its effects gain or look for,
+ * Gardens (scored by the player's card count), and Colony (which ends the game like Province, if it's loaded).
+ * Looked up once when the registry is made, so the rules compare ids rather than names.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct KnownCards {
+    pub copper: CardId,
+    pub silver: CardId,
+    pub gold: CardId,
+    pub estate: CardId,
+    pub duchy: CardId,
+    pub province: CardId,
+    pub curse: CardId,
+    pub gardens: CardId,
+    pub colony: Option<CardId>
+}
+
+/**
+ * CardRegistry
+ * Every loaded card. A card's CardId is its index here.
+ * Also keeps the kingdom cards (everything outside the basic supply) in cost order, for picking kingdoms from,
+ * and the ids of the cards the rules mention.
+ */
+pub struct CardRegistry {
+    cards: Vec<CardProperties>,
+    kingdom: Vec<CardId>,
+    known: KnownCards
+}
+
+/**
+ * CardRegistry implementation
+ */
+impl CardRegistry {
+    /**
+     * new
+     * Make a registry of the given cards, making sure it has every card the rules mention (the basic supply, and Gardens).
+     * Other kingdom cards are optional; a kingdom that names one that isn't loaded fails when it's chosen.
+     * Returns an error listing each missing card.
+     */
+    pub fn new(cards: Vec<CardProperties>, source: &str) -> Result<CardRegistry, CardDataError> {
+        let find = |name: &str| cards.iter().position(|c| c.name == name).map(|i| CardId(i as u16));
+
+        let mut problems = Vec::new();
+        for name in BASIC_SUPPLY.iter().chain(["Gardens"].iter()) {
+            if find(name).is_none() {
+                problems.push(format!("{}: missing, but the game needs it.", name));
+            }
+        }
+        if cards.len() > u16::MAX as usize {
+            problems.push(format!("There are {} cards, but at most {} are supported.", cards.len(), u16::MAX));
+        }
+
+        if !problems.is_empty() {
+            return Err(CardDataError {source: String::from(source), problems: problems});
+        }
+
+        let required = |name: &str| find(name).expect("Every card the game needs was checked for above.");
+        let known = KnownCards {
+            copper: required("Copper"),
+            silver: required("Silver"),
+            gold: required("Gold"),
+            estate: required("Estate"),
+            duchy: required("Duchy"),
+            province: required("Province"),
+            curse: required("Curse"),
+            gardens: required("Gardens"),
+            colony: find("Colony")
+        };
+
+        // The kingdom pool, cheapest first (and by name within a cost), so it doesn't depend on the order cards were loaded in.
+        let mut kingdom: Vec<CardId> = (0..cards.len())
+            .filter(|i| !BASIC_SUPPLY.contains(&cards[*i].name.as_str()))
+            .map(|i| CardId(i as u16))
+            .collect();
+        kingdom.sort_by(|a, b| {
+            let (a, b) = (&cards[a.0 as usize], &cards[b.0 as usize]);
+            a.cost.cmp(&b.cost).then_with(|| a.name.cmp(&b.name))
+        });
+
+        return Ok(CardRegistry {cards: cards, kingdom: kingdom, known: known});
+    }
+
+    /**
+     * get
+     * Get a card's details by its id.
+     * Ids only come from this registry, so they're always valid.
+     */
+    pub fn get(&self, id: CardId) -> &CardProperties {
+        return &self.cards[id.0 as usize];
+    }
+
+    /**
+     * find
+     * Find the id of a card by name.
+     * Returns nothing if there's no card with that name.
+     */
+    pub fn find(&self, card_name: &str) -> Option<CardId> {
+        return self.cards.iter().position(|c| c.name == card_name).map(|i| CardId(i as u16));
+    }
+
+    /**
+     * get_card_ids
+     * The ids of every card in the registry, in the order they were loaded.
+     */
+    pub fn get_card_ids(&self) -> Vec<CardId> {
+        return (0..self.cards.len()).map(|i| CardId(i as u16)).collect();
+    }
+
+    /**
+     * get_kingdom_cards
+     * Every card that can go in the kingdom (anything loaded outside the basic supply), cheapest first, then by name.
+     */
+    pub fn get_kingdom_cards(&self) -> &[CardId] {
+        return &self.kingdom;
+    }
+
+    /**
+     * get_known_cards
+     * The ids of the cards the game's rules mention by name.
+     */
+    pub fn get_known_cards(&self) -> &KnownCards {
+        return &self.known;
+    }
+
+    /**
+     * get_card_names
+     * The names of every card in the registry, in the order they were loaded.
+     */
+    pub fn get_card_names(&self) -> Vec<String> {
+        return self.cards.iter().map(|c| c.name.clone()).collect();
+    }
+}
+
+/**
+ * load_card_registry
+ * Load the cards every game will use from a file, in place of the built-in base set.
+ * Has to be called before the first game is made; once cards are in use they can't be swapped.
+ */
+pub fn load_card_registry(path: &str) -> Result<(), CardDataError> {
+    let registry = CardRegistry::new(load_cards_from_file(path)?, path)?;
+
+    if CARD_REGISTRY.set(registry).is_err() {
+        return Err(CardDataError {source: String::from(path), problems: vec![String::from("Cards are already loaded, so they can't be replaced.")]});
+    }
+
+    return Ok(());
+}
+
+/**
+ * card_registry
+ * The cards every game is played with. Uses the built-in base set if no other cards were loaded.
+ */
+pub fn card_registry() -> &'static CardRegistry {
+    return CARD_REGISTRY.get_or_init(|| {
+        let cards = load_cards_from_toml(BASE_SET_CARDS, "the built-in base set").expect("The built-in cards should always load.");
+        return CardRegistry::new(cards, "the built-in base set").expect("The built-in cards should have every card the game needs.");
+    });
+}
+
+/**
+ * Registry tests
+ * Registries made from part of the base set: they need the cards the rules mention, and nothing more.
+ */
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+
+    // The built-in cards with the given names, in the order they're written.
+    fn base_cards(names: &[&str]) -> Vec<CardProperties> {
+        let cards = load_cards_from_toml(BASE_SET_CARDS, "the built-in base set").expect("The built-in cards should always load.");
+        return cards.into_iter().filter(|c| names.contains(&c.name.as_str())).collect();
+    }
+
+    #[test]
+    fn the_basic_supply_and_gardens_are_enough() {
+        let names = ["Copper", "Silver", "Gold", "Estate", "Duchy", "Province", "Curse", "Gardens", "Smithy"];
+        let registry = CardRegistry::new(base_cards(&names), "test").expect("The registry has every card the rules mention.");
+
+        assert_eq!(registry.get_kingdom_cards().len(), 2);
+        assert!(registry.get_known_cards().colony.is_none());
+    }
+
+    #[test]
+    fn every_missing_card_the_rules_mention_is_listed() {
+        let names = ["Silver", "Gold", "Estate", "Duchy", "Province", "Curse", "Smithy"];
+        let error = match CardRegistry::new(base_cards(&names), "test") {
+            Ok(_) => panic!("The registry is missing cards the rules mention."),
+            Err(error) => error
+        };
+
+        assert_eq!(error.problems, ["Copper: missing, but the game needs it.", "Gardens: missing, but the game needs it."]);
+    }
+}
@@ -5,18 +5,23 @@ card_structures.rs
 Implementation of the complex "Pile" struct, representing a homogenous pile of cards.
 */
 
+use serde::{Deserialize, Serialize};
+
 use crate::game::game_errors::OutOfCardsError;
 use crate::card_manager::card::Card;
+use crate::card_manager::card_properties::CardTypes;
+use crate::card_manager::card_registry::CardId;
 
 /**
  * Pile struct
  * A pile represents a stack of one card.
- * Instead of creating the cards at runtime and hogging memory resources, the pile contains a builder method, and returns one card at a time.
+ * Instead of creating the cards at runtime and hogging memory resources, the pile holds the card's id, and hands the id out one card at a time.
+ * It's just the id and a count, so the whole supply is a small array of plain values.
  */
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Pile {
-    pub pile_name: String,
     pub cards_left: u8,
-    pub card_creator: fn() -> Box<dyn Card>
+    pub card: CardId
 }
 
 /**
@@ -36,10 +41,10 @@ impl Pile {
      * Pick up a card from the pile.
      * If there are no more cards, return an OutOfCards error.
      */
-    pub fn pick_up_card(&mut self) -> Result<Box<dyn Card>, OutOfCardsError> {
+    pub fn pick_up_card(&mut self) -> Result<CardId, OutOfCardsError> {
         if self.cards_left > 0 {
             self.cards_left -= 1;
-            let a: Box<dyn Card> = (self.card_creator)();
+            let a: CardId = self.card;
             Ok(a)
         } 
         else {
@@ -60,7 +65,7 @@ impl Pile {
      * Getter method for the name of the pile.
      */
     pub fn get_pile_name(&self) -> String {
-        return self.pile_name.clone();
+        return format!("{} Pile", self.card.get_name());
     }
 
     // Each of the below card getter methods could likely be implemented using a immutable reference,
@@ -71,8 +76,7 @@ impl Pile {
      * Getter method for the card's description.
      */
     pub fn get_card_description(&self) -> String {
-        let demo_card = (self.card_creator)();
-        return demo_card.get_description().to_string();
+        return self.card.get_description().to_string();
     }
 
     /**
@@ -80,8 +84,15 @@ impl Pile {
      * Getter method for the card's price.
      */
     pub fn get_card_price(&self) -> i32 {
-        let demo_card = (self.card_creator)();
-        return demo_card.get_cost();
+        return self.card.get_cost();
+    }
+
+    /**
+     * get_card_types
+     * Getter method for the card's types.
+     */
+    pub fn get_card_types(&self) -> &[CardTypes] {
+        return self.card.get_card_types();
     }
 
     /**
@@ -89,8 +100,7 @@ impl Pile {
      * Getter method for card's name
      */
     pub(crate) fn get_card_name(&self) -> String {
-        let demo_card = (self.card_creator)();
-        return demo_card.get_name().to_string();
+        return self.card.get_name().to_string();
     }
 }
 
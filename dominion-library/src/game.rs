@@ -7,6 +7,17 @@ Reference module, required by rust to reference modules in folder structures.
 
 pub mod game_manager;
 pub mod board;
+pub mod card_effects;
+pub mod game_command;
 pub mod game_errors;
-pub mod player_middleware;
-pub mod pile_builder;
\ No newline at end of file
+pub mod game_event;
+pub mod game_log;
+pub mod game_results;
+pub mod game_state;
+pub mod kingdom;
+pub mod kingdom_constraints;
+pub mod pile_builder;
+pub mod replay;
+pub mod save_file;
+pub mod turn_controller;
+pub mod undo;
\ No newline at end of file
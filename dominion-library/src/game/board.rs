@@ -5,9 +5,14 @@ board.rs
 Defines the main game board with piles of kingdom cards, victory cards, treasure cards.
 */
 
+use serde::{Deserialize, Serialize};
+
 use crate::card_manager::card::Card;
+use crate::card_manager::card_properties::CardTypes;
 use crate::card_manager::card_structures::Pile;
+use crate::card_manager::card_registry::{card_registry, CardId};
 use crate::game::game_errors::*;
+use crate::player::agent::SupplyChoice;
 
 /**
  * CardSet
@@ -18,7 +23,7 @@ use crate::game::game_errors::*;
  * Kingdoms row 2
  * This is an enum to say which set a caller method is referring to, for clarity's sake.
  */
-#[derive (Clone)]
+#[derive (Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CardSet {
     Victories,
     Treasures,
@@ -29,13 +34,14 @@ pub enum CardSet {
  * Board
  * This is a board struct filled with vectors of piles.
  * It stores the kingdom set as one vector for simplicity,
- *  And has an (currently) unused trash pile.
+ *  And has the trash, where trashed cards go for the rest of the game.
  */
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Board {
     pub(crate) victory_cards: Vec<Pile>,
     pub(crate) treasure_cards: Vec<Pile>,
     pub(crate) kingdom_set: Vec<Pile>,
-    pub(crate) trash: Vec<Box<dyn Card>>
+    pub(crate) trash: Vec<CardId>
 }
 
 
@@ -44,6 +50,18 @@ pub struct Board {
  */
 impl Board {
 
+    /**
+     * empty_piles_to_end
+     * How many supply piles need to be empty to end the game.
+     * Three normally, or four with five or more players.
+     */
+    pub(crate) fn empty_piles_to_end(player_count: usize) -> usize {
+        if player_count >= 5 {
+            return 4;
+        }
+        return 3;
+    }
+
     /**
      * get_mut_pile
      * Get a mutable pile from the board.
@@ -99,19 +117,118 @@ impl Board {
         
     }
 
+    /**
+     * get_trash_names
+     * The names of every card in the trash, in the order they were trashed.
+     */
+    pub fn get_trash_names(&self) -> Vec<String> {
+        return self.trash.iter().map(|c| c.get_name().clone()).collect();
+    }
+
+    /**
+     * get_trash_count
+     * How many cards are in the trash.
+     */
+    pub fn get_trash_count(&self) -> usize {
+        return self.trash.len();
+    }
+
+    /**
+     * count_in_trash
+     * How many copies of a card are in the trash.
+     */
+    pub fn count_in_trash(&self, card: CardId) -> usize {
+        return self.trash.iter().filter(|c| **c == card).count();
+    }
+
+    /**
+     * find_pile
+     * Find the supply pile holding a card, as an index and the set it's in.
+     */
+    pub(crate) fn find_pile(&self, card: CardId) -> Option<(usize, CardSet)> {
+        let sets = [
+            (CardSet::Victories, &self.victory_cards),
+            (CardSet::Treasures, &self.treasure_cards),
+            (CardSet::Kingdoms, &self.kingdom_set)
+        ];
+
+        for (set, piles) in sets {
+            for (index, pile) in piles.iter().enumerate() {
+                if pile.card == card {
+                    return Some((index, set));
+                }
+            }
+        }
+
+        return None;
+    }
+
+    /**
+     * get_supply_choices
+     * Every supply pile that still has cards, and costs at most max_cost (optionally, only piles of one card type).
+     * Used by cards that let a player gain a card.
+     */
+    pub(crate) fn get_supply_choices(&self, max_cost: i32, card_type: Option<CardTypes>) -> Vec<SupplyChoice> {
+        let mut choices = Vec::new();
+        let sets = [
+            (CardSet::Victories, &self.victory_cards),
+            (CardSet::Treasures, &self.treasure_cards),
+            (CardSet::Kingdoms, &self.kingdom_set)
+        ];
+
+        for (set, piles) in sets {
+            for (index, pile) in piles.iter().enumerate() {
+                if pile.is_empty() || pile.get_card_price() > max_cost {
+                    continue;
+                }
+                if let Some(t) = &card_type {
+                    if !pile.get_card_types().contains(t) {
+                        continue;
+                    }
+                }
+
+                choices.push(SupplyChoice {
+                    set: set,
+                    index: index,
+                    name: pile.get_card_name(),
+                    cost: pile.get_card_price()
+                });
+            }
+        }
+
+        return choices;
+    }
+
     /**
      * check_ending
      * This function is meant to be called at the end of every turn.
-     * checks if the game has ended, and either the province pile is out, or two piles are empty.
+     * checks if the game has ended: either the province (or colony) pile is out,
+     * or enough supply piles are empty (three, or four with five or more players).
      */
-    pub(crate) fn check_ending(&self) -> bool {
-        // Check the province pile.
-        if self.victory_cards[2].is_empty() {
+    pub(crate) fn check_ending(&self, player_count: usize) -> bool {
+        // Check the province and colony piles, found by the card they hold rather than where they sit.
+        let known = card_registry().get_known_cards();
+        for pile in self.victory_cards.iter().chain(self.treasure_cards.iter()).chain(self.kingdom_set.iter()) {
+            let ends_game = pile.card == known.province || Some(pile.card) == known.colony;
+            if ends_game && pile.is_empty() {
+                return true;
+            }
+        }
+
+        if self.count_empty_piles() >= Board::empty_piles_to_end(player_count) {
             return true;
         }
 
+        return false;
+    }
+
+    /**
+     * count_empty_piles
+     * How many supply piles have run out.
+     */
+    pub(crate) fn count_empty_piles(&self) -> usize {
         // Initialize a counter accross ALL piles.
-        let mut counter: u8 = 0;
+        let mut counter: usize = 0;
         // Check the treasures.
         for pile in &self.treasure_cards {
             if pile.is_empty() {
@@ -119,7 +236,7 @@ impl Board {
             }
         }
 
-        // Check the victories.
+        // Check the victories (the curse pile sits with them, and counts like any other pile).
         for pile in &self.victory_cards {
             if pile.is_empty() {
                 counter += 1;
@@ -135,11 +252,7 @@ impl Board {
 
         }
 
-        if counter >= 2 {
-            return true;
-        }
-
-        return false;
+        return counter;
     }
 }
 
@@ -149,7 +262,7 @@ impl Board {
  * Realistically, the player needs a LOT more information, so the ui layer currently has ownership, and accesses properties directly.
  */
 pub trait PlayerInterface {
-    fn buy_card(self: &mut Self, index: usize, c: CardSet) -> Result<Box<dyn Card>, GameErrors>;
+    fn buy_card(&mut self, index: usize, c: CardSet) -> Result<CardId, GameErrors>;
     fn get_card_price(&self, index: usize, c: CardSet) -> Result<i32, GameErrors>;
     fn get_pile_desc(&self, index: usize, c:CardSet) -> Result<String, GameErrors>;
 }
@@ -161,7 +274,7 @@ impl PlayerInterface for Board {
     /**
      * Buy card method.
      */
-    fn buy_card(self: &mut Self, index: usize, c: CardSet) -> Result<Box<dyn Card>, GameErrors> {
+    fn buy_card(&mut self, index: usize, c: CardSet) -> Result<CardId, GameErrors> {
         // Get the pile from the index and cardset.
         let pile_op = self.get_mut_pile(index, c);
 
@@ -0,0 +1,979 @@
+/*
+SPUStudnet
+12/15/2024
+card_effects.rs
+Resolves the special effects of action cards (anything beyond +Cards, +Actions, +Buys and +Coins).
+Effects are resolved one step at a time off of a stack, so they can stop and wait whenever a player has to make a decision.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::card_manager::card::Card;
+use crate::card_manager::card_properties::{ActionEvents, CardTypes};
+use crate::card_manager::card_registry::{card_registry, CardId};
+use crate::game::game_event::GameEvent;
+use crate::game::game_state::GameState;
+use crate::player::agent::{Decision, DecisionRequest};
+use crate::player::player::{GainDestination, Player};
+
+/**
+ * EffectFrame
+ * One card effect partway through being resolved.
+ * The stage says how far along it is, so it can pick up where it left off once a decision comes back.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct EffectFrame {
+    // Which card effect this is.
+    pub(crate) effect: ActionEvents,
+    // The seat of the player who played the card.
+    pub(crate) seat: usize,
+    // How far through the effect we are.
+    pub(crate) stage: u8,
+    // Anything the effect needs to remember between stages (like the cost of a trashed card).
+    pub(crate) value: i32,
+    // The seat an attack is hitting, for frames that resolve an attack against one player.
+    pub(crate) target: Option<usize>,
+    // The seats that revealed a reaction to block this attack.
+    pub(crate) protected: Vec<usize>,
+    // Cards taken off of a deck partway through an effect (like the cards Bandit reveals).
+    pub(crate) revealed: Vec<CardId>
+}
+
+/**
+ * EffectFrame implementation
+ */
+impl EffectFrame {
+    /**
+     * Create a frame for an effect that hasn't started yet.
+     */
+    pub(crate) fn new(effect: ActionEvents, seat: usize) -> EffectFrame {
+        EffectFrame {
+            effect: effect,
+            seat: seat,
+            stage: 0,
+            value: 0,
+            target: None,
+            protected: Vec::new(),
+            revealed: Vec::new()
+        }
+    }
+
+    /**
+     * Create a frame for an attack hitting one player.
+     */
+    pub(crate) fn against(effect: ActionEvents, seat: usize, target: usize) -> EffectFrame {
+        let mut frame = EffectFrame::new(effect, seat);
+        frame.target = Some(target);
+        return frame;
+    }
+}
+
+/**
+ * TreasureTrigger
+ * Something waiting on its player to play a treasure this turn.
+ * Set up by cards like Merchant, and thrown away at cleanup.
+ */
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum TreasureTrigger {
+    // + 1 Coin the first time a Silver is played this turn (from Merchant).
+    FirstSilver
+}
+
+/**
+ * fire_treasure_triggers
+ * Called whenever a player plays a treasure.
+ * Fires (and uses up) any of their triggers the treasure sets off.
+ */
+pub(crate) fn fire_treasure_triggers(player: &mut Player, treasure: CardId) {
+    if treasure != card_registry().get_known_cards().silver {
+        return;
+    }
+
+    player.silvers_played += 1;
+    if player.silvers_played == 1 {
+        let merchants = player.treasure_triggers.iter().filter(|t| **t == TreasureTrigger::FirstSilver).count();
+        player.buy_power += merchants as u8;
+    }
+    player.treasure_triggers.retain(|t| *t != TreasureTrigger::FirstSilver);
+}
+
+// Stages every attack goes through once the attacker's own part of the card is done.
+// First each opponent (in turn order) gets a chance to react, then the attack hits everyone who didn't block it.
+const ATTACK_REACT: u8 = 100;
+const ATTACK_REVEALED: u8 = 101;
+const ATTACK_HIT: u8 = 102;
+
+/**
+ * EffectStep
+ * What happened when an effect took a step.
+ */
+pub(crate) enum EffectStep {
+    // The effect is finished.
+    Done,
+    // The effect isn't finished, take another step.
+    Continue(EffectFrame),
+    // The effect needs the given seat to make a decision before it can take another step.
+    Ask(EffectFrame, usize, DecisionRequest),
+    // Resolve the second effect first, then carry on with the first.
+    Then(EffectFrame, EffectFrame)
+}
+
+/**
+ * step
+ * Take one step of an effect.
+ * answer holds the decision the effect asked for on its last step, if it asked for one.
+ */
+pub(crate) fn step(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    // Attacks share the same reaction and hitting stages, whichever card they came from.
+    if frame.target.is_none() && frame.stage >= ATTACK_REACT {
+        return attack(state, frame, answer);
+    }
+
+    match (frame.effect, frame.target) {
+        (ActionEvents::Chapel, _) => chapel(state, frame, answer),
+        (ActionEvents::Remodel, _) => remodel(state, frame, answer),
+        (ActionEvents::Mine, _) => mine(state, frame, answer),
+        (ActionEvents::Moneylender, _) => moneylender(state, frame, answer),
+        (ActionEvents::Militia, None) | (ActionEvents::Witch, None) => start_attack(frame),
+        (ActionEvents::Militia, Some(_)) => militia_hit(state, frame, answer),
+        (ActionEvents::Witch, Some(_)) => witch_hit(state, frame),
+        (ActionEvents::Bandit, None) => bandit(state, frame),
+        (ActionEvents::Bandit, Some(_)) => bandit_hit(state, frame, answer),
+        (ActionEvents::Workshop, _) => workshop(state, frame, answer),
+        (ActionEvents::Merchant, _) => merchant(state, frame),
+        (ActionEvents::Cellar, _) => cellar(state, frame, answer),
+        (ActionEvents::Harbinger, _) => harbinger(state, frame, answer),
+        (ActionEvents::Vassal, _) => vassal(state, frame, answer),
+        (ActionEvents::Bureaucrat, None) => bureaucrat(state, frame),
+        (ActionEvents::Bureaucrat, Some(_)) => bureaucrat_hit(state, frame, answer),
+        (ActionEvents::Poacher, _) => poacher(state, frame, answer),
+        (ActionEvents::ThroneRoom, _) => throne_room(state, frame, answer),
+        (ActionEvents::CouncilRoom, _) => council_room(state, frame),
+        (ActionEvents::Library, _) => library(state, frame, answer),
+        (ActionEvents::Sentry, _) => sentry(state, frame, answer),
+        (ActionEvents::Artisan, _) => artisan(state, frame, answer),
+        (ActionEvents::No, _) => EffectStep::Done
+    }
+}
+
+/**
+ * ask
+ * Helper: move the frame on to its next stage, and ask a decision of the player the frame is acting on first.
+ * That's the player an attack is hitting, or the player who played the card otherwise.
+ */
+fn ask(mut frame: EffectFrame, request: DecisionRequest) -> EffectStep {
+    let seat = frame.target.unwrap_or(frame.seat);
+    frame.stage += 1;
+    return EffectStep::Ask(frame, seat, request);
+}
+
+/**
+ * start_attack
+ * Helper: the attacker's part of the card is done, so move on to the other players' reactions.
+ */
+fn start_attack(mut frame: EffectFrame) -> EffectStep {
+    frame.stage = ATTACK_REACT;
+    // The value counts around the table from the attacker, starting with the next seat.
+    frame.value = 1;
+    return EffectStep::Continue(frame);
+}
+
+/**
+ * attack
+ * Visits every other player in turn order, giving them a chance to reveal a reaction (like Moat) to be unaffected.
+ * Then visits them again, resolving the attack against everyone who didn't.
+ */
+fn attack(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    let player_count = state.players.len();
+    let target = (seat + frame.value as usize) % player_count;
+
+    match frame.stage {
+        ATTACK_REACT => {
+            // Everyone has had their chance to react, so go back around the table hitting them.
+            if frame.value as usize >= player_count {
+                frame.stage = ATTACK_HIT;
+                frame.value = 1;
+                return EffectStep::Continue(frame);
+            }
+
+            match state.players[target].hand.iter().find(|c| c.get_card_types().contains(&CardTypes::Reaction)) {
+                Some(reaction) => {
+                    let prompt = format!("{} is attacking you. Reveal {} to be unaffected?", state.players[seat].name, reaction.get_name());
+                    frame.stage = ATTACK_REVEALED;
+                    return EffectStep::Ask(frame, target, DecisionRequest::YesNo { prompt: prompt });
+                }
+                None => {
+                    frame.value += 1;
+                    return EffectStep::Continue(frame);
+                }
+            }
+        }
+        ATTACK_REVEALED => {
+            if let Some(Decision::YesNo(true)) = answer {
+                if let Some(reaction) = state.players[target].hand.iter().find(|c| c.get_card_types().contains(&CardTypes::Reaction)).copied() {
+                    state.reveal_card(target, reaction);
+                    frame.protected.push(target);
+                }
+            }
+            frame.stage = ATTACK_REACT;
+            frame.value += 1;
+            return EffectStep::Continue(frame);
+        }
+        _ => {
+            if frame.value as usize >= player_count {
+                return EffectStep::Done;
+            }
+            frame.value += 1;
+            if frame.protected.contains(&target) {
+                return EffectStep::Continue(frame);
+            }
+
+            let hit = EffectFrame::against(frame.effect, seat, target);
+            return EffectStep::Then(frame, hit);
+        }
+    }
+}
+
+/**
+ * trash_picks
+ * Helper: trash the picked cards from a player's hand.
+ * Goes from the highest index down, so removing a card doesn't move the ones still to be trashed.
+ */
+fn trash_picks(state: &mut GameState, seat: usize, mut picks: Vec<usize>) -> i32 {
+    let mut cost = 0;
+    picks.sort();
+    for index in picks.into_iter().rev() {
+        if let Ok(card) = state.trash_from_hand(seat, index) {
+            cost = card.get_cost();
+        }
+    }
+    return cost;
+}
+
+/**
+ * discard_picks
+ * Helper: discard the picked cards from a player's hand, highest index first.
+ */
+fn discard_picks(state: &mut GameState, seat: usize, mut picks: Vec<usize>) {
+    picks.sort();
+    for index in picks.into_iter().rev() {
+        if index < state.players[seat].hand.len() {
+            let card = state.players[seat].hand.remove(index);
+            state.discard_card(seat, card);
+        }
+    }
+}
+
+/**
+ * play_for_free
+ * Helper: put an action card into play and give the player its basic properties, without using up an action.
+ * Returns the frame for the card's effect, if it has one, so the caller can resolve it next.
+ */
+fn play_for_free(state: &mut GameState, seat: usize, card: CardId) -> Option<EffectFrame> {
+    let props = card.get_action_properties();
+    state.players[seat].in_play.push(card);
+    state.emit(GameEvent::CardPlayed { seat: seat, card: card });
+    state.players[seat].apply_action_properties(&props);
+
+    if props.event == ActionEvents::No {
+        return None;
+    }
+    return Some(EffectFrame::new(props.event, seat));
+}
+
+/**
+ * gain_from_choices
+ * Helper: gain the picked supply pile, out of every pile that costs at most max_cost.
+ * The choices are worked out the same way they were when the player was asked, so the index lines up.
+ */
+fn gain_from_choices(state: &mut GameState, seat: usize, max_cost: i32, card_type: Option<CardTypes>, pick: usize, destination: GainDestination) {
+    let choices = state.board.get_supply_choices(max_cost, card_type);
+    if let Some(choice) = choices.get(pick) {
+        let _ = state.gain_card(seat, choice.index, choice.set, destination);
+    }
+}
+
+/**
+ * Chapel: trash up to 4 cards from your hand.
+ */
+fn chapel(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            if state.players[seat].hand.is_empty() {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Chapel: choose up to 4 cards to trash."),
+                min: 0,
+                max: 4,
+                card_type: None
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            trash_picks(state, seat, picks);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Remodel: trash a card from your hand, and gain a card costing up to 2 more than it.
+ */
+fn remodel(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            if state.players[seat].hand.is_empty() {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Remodel: choose a card to trash."),
+                min: 1,
+                max: 1,
+                card_type: None
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            frame.value = trash_picks(state, seat, picks) + 2;
+            let choices = state.board.get_supply_choices(frame.value, None);
+            if choices.is_empty() {
+                return EffectStep::Done;
+            }
+            let max_cost = frame.value;
+            let prompt = format!("Remodel: gain a card costing up to {}.", max_cost);
+            return ask(frame, DecisionRequest::ChooseSupplyPile { prompt: prompt, max_cost: max_cost, choices: choices, optional: false });
+        }
+        (2, Some(Decision::SupplyPile(Some(pick)))) => {
+            gain_from_choices(state, seat, frame.value, None, pick, GainDestination::Discard);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Mine: you may trash a treasure from your hand, to gain a treasure to your hand costing up to 3 more than it.
+ */
+fn mine(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            if !state.players[seat].hand.iter().any(|c| c.get_card_types().contains(&CardTypes::Treasure)) {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Mine: you may choose a treasure to trash."),
+                min: 0,
+                max: 1,
+                card_type: Some(CardTypes::Treasure)
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            if picks.is_empty() {
+                return EffectStep::Done;
+            }
+            frame.value = trash_picks(state, seat, picks) + 3;
+            let choices = state.board.get_supply_choices(frame.value, Some(CardTypes::Treasure));
+            if choices.is_empty() {
+                return EffectStep::Done;
+            }
+            let max_cost = frame.value;
+            let prompt = format!("Mine: gain a treasure to your hand costing up to {}.", max_cost);
+            return ask(frame, DecisionRequest::ChooseSupplyPile { prompt: prompt, max_cost: max_cost, choices: choices, optional: false });
+        }
+        (2, Some(Decision::SupplyPile(Some(pick)))) => {
+            gain_from_choices(state, seat, frame.value, Some(CardTypes::Treasure), pick, GainDestination::Hand);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Moneylender: you may trash a copper from your hand for +3 coins.
+ */
+fn moneylender(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    let copper_id = card_registry().get_known_cards().copper;
+    let copper = state.players[seat].hand.iter().position(|c| *c == copper_id);
+    match (frame.stage, answer, copper) {
+        (_, _, None) => EffectStep::Done,
+        (0, _, Some(_)) => {
+            return ask(frame, DecisionRequest::YesNo { prompt: String::from("Moneylender: trash a Copper for +3 coins?") });
+        }
+        (1, Some(Decision::YesNo(true)), Some(index)) => {
+            if state.trash_from_hand(seat, index).is_ok() {
+                state.players[seat].buy_power += 3;
+            }
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Workshop: gain a card costing up to 4.
+ */
+fn workshop(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            let choices = state.board.get_supply_choices(4, None);
+            if choices.is_empty() {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseSupplyPile {
+                prompt: String::from("Workshop: gain a card costing up to 4."),
+                max_cost: 4,
+                choices: choices,
+                optional: false
+            });
+        }
+        (1, Some(Decision::SupplyPile(Some(pick)))) => {
+            gain_from_choices(state, seat, 4, None, pick, GainDestination::Discard);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Merchant: the first time you play a Silver this turn, + 1 Coin.
+ * The + 1 Card and + 1 Action are basic properties, so all that's left is to set up the trigger.
+ * If a Silver has already been played this turn, it's too late for this Merchant.
+ */
+fn merchant(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    let player = &mut state.players[frame.seat];
+    if player.silvers_played == 0 {
+        player.treasure_triggers.push(TreasureTrigger::FirstSilver);
+    }
+    return EffectStep::Done;
+}
+
+/**
+ * Militia (against one player): discard down to 3 cards in hand.
+ */
+fn militia_hit(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let target = frame.target.unwrap_or(frame.seat);
+    match (frame.stage, answer) {
+        (0, _) => {
+            let excess = state.players[target].hand.len().saturating_sub(3);
+            if excess == 0 {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: format!("Militia: discard {} cards, down to 3 in hand.", excess),
+                min: excess,
+                max: excess,
+                card_type: None
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            discard_picks(state, target, picks);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Witch (against one player): gain a Curse.
+ */
+fn witch_hit(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    let target = frame.target.unwrap_or(frame.seat);
+    let curse_pile = state.board.find_pile(card_registry().get_known_cards().curse);
+    if let Some((index, set)) = curse_pile {
+        // An empty curse pile just means there's nothing left to give.
+        let _ = state.gain_card(target, index, set, GainDestination::Discard);
+    }
+    return EffectStep::Done;
+}
+
+/**
+ * Bandit: gain a Gold, then attack.
+ */
+fn bandit(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    let gold_pile = state.board.find_pile(card_registry().get_known_cards().gold);
+    if let Some((index, set)) = gold_pile {
+        let _ = state.gain_card(frame.seat, index, set, GainDestination::Discard);
+    }
+    return start_attack(frame);
+}
+
+/**
+ * Bandit (against one player): reveal the top 2 cards of your deck, trash a revealed Treasure other than Copper, and discard the rest.
+ */
+fn bandit_hit(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let target = frame.target.unwrap_or(frame.seat);
+    match (frame.stage, answer) {
+        (0, _) => {
+            for _ in 0..2 {
+                if let Some(card) = state.players[target].take_from_deck_top() {
+                    state.reveal_card(target, card);
+                    frame.revealed.push(card);
+                }
+            }
+
+            // Only ask which to trash if there's a real choice to make.
+            let treasures: Vec<usize> = (0..frame.revealed.len()).filter(|i| bandit_can_trash(&frame.revealed[*i])).collect();
+            if treasures.len() > 1 && frame.revealed[treasures[0]] != frame.revealed[treasures[1]] {
+                let cards = treasures.iter().map(|i| frame.revealed[*i].get_name().clone()).collect();
+                return ask(frame, DecisionRequest::ChooseCards {
+                    prompt: String::from("Bandit: choose a treasure to trash."),
+                    cards: cards,
+                    min: 1,
+                    max: 1
+                });
+            }
+
+            let trash_index = treasures.first().copied();
+            bandit_finish(state, target, frame.revealed, trash_index);
+            return EffectStep::Done;
+        }
+        (_, answer) => {
+            let treasures: Vec<usize> = (0..frame.revealed.len()).filter(|i| bandit_can_trash(&frame.revealed[*i])).collect();
+            let pick = match answer {
+                Some(Decision::Cards(picks)) if !picks.is_empty() => picks[0],
+                _ => 0
+            };
+            let trash_index = treasures.get(pick).copied();
+            bandit_finish(state, target, frame.revealed, trash_index);
+            return EffectStep::Done;
+        }
+    }
+}
+
+/**
+ * Helper: can Bandit trash this card? Any treasure other than Copper.
+ */
+fn bandit_can_trash(card: &CardId) -> bool {
+    return card.get_card_types().contains(&CardTypes::Treasure) && *card != card_registry().get_known_cards().copper;
+}
+
+/**
+ * Helper: trash the chosen revealed card (if any), and discard the rest.
+ */
+fn bandit_finish(state: &mut GameState, target: usize, revealed: Vec<CardId>, trash_index: Option<usize>) {
+    for (i, card) in revealed.into_iter().enumerate() {
+        if Some(i) == trash_index {
+            state.trash_card(target, card);
+        }
+        else {
+            state.discard_card(target, card);
+        }
+    }
+}
+
+/**
+ * Cellar: discard any number of cards, then draw that many.
+ */
+fn cellar(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            let hand_size = state.players[seat].hand.len();
+            if hand_size == 0 {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Cellar: discard any number of cards, then draw that many."),
+                min: 0,
+                max: hand_size,
+                card_type: None
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            let count = picks.len();
+            discard_picks(state, seat, picks);
+            state.draw_cards(seat, count);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Harbinger: look through your discard pile. You may put a card from it onto your deck.
+ */
+fn harbinger(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            if state.players[seat].discard.is_empty() {
+                return EffectStep::Done;
+            }
+            let cards = state.players[seat].discard.iter().map(|c| c.get_name().clone()).collect();
+            return ask(frame, DecisionRequest::ChooseCards {
+                prompt: String::from("Harbinger: you may put a card from your discard pile onto your deck."),
+                cards: cards,
+                min: 0,
+                max: 1
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            if let Some(card) = picks.first().and_then(|i| state.players[seat].discard.remove(*i)) {
+                state.players[seat].deck.push_front(card);
+            }
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Vassal: discard the top card of your deck. If it's an action card, you may play it.
+ */
+fn vassal(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            let card = match state.players[seat].take_from_deck_top() {
+                Some(c) => c,
+                None => return EffectStep::Done
+            };
+
+            let is_action = card.get_card_types().contains(&CardTypes::Action);
+            let prompt = format!("Vassal: play the {} you discarded?", card.get_name());
+            state.discard_card(seat, card);
+            if !is_action {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::YesNo { prompt: prompt });
+        }
+        (1, Some(Decision::YesNo(true))) => {
+            // The discarded card is still on top of the discard pile.
+            let card = match state.players[seat].discard.pop_front() {
+                Some(c) => c,
+                None => return EffectStep::Done
+            };
+            match play_for_free(state, seat, card) {
+                Some(effect) => EffectStep::Continue(effect),
+                None => EffectStep::Done
+            }
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Bureaucrat: gain a Silver onto your deck, then attack.
+ */
+fn bureaucrat(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    let silver_pile = state.board.find_pile(card_registry().get_known_cards().silver);
+    if let Some((index, set)) = silver_pile {
+        let _ = state.gain_card(frame.seat, index, set, GainDestination::DeckTop);
+    }
+    return start_attack(frame);
+}
+
+/**
+ * Bureaucrat (against one player): reveal a victory card from your hand and put it onto your deck,
+ * or reveal a hand with no victory cards.
+ */
+fn bureaucrat_hit(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let target = frame.target.unwrap_or(frame.seat);
+    let victories: Vec<usize> = (0..state.players[target].hand.len()).filter(|i| state.players[target].hand[*i].get_card_types().contains(&CardTypes::Victory)).collect();
+    match (frame.stage, answer) {
+        (0, _) => {
+            if victories.is_empty() {
+                for i in 0..state.players[target].hand.len() {
+                    let card = state.players[target].hand[i];
+                    state.reveal_card(target, card);
+                }
+                return EffectStep::Done;
+            }
+
+            // Only ask which one if there's a real choice to make.
+            let first = state.players[target].hand[victories[0]];
+            if victories.iter().all(|i| state.players[target].hand[*i] == first) {
+                bureaucrat_topdeck(state, target, victories[0]);
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Bureaucrat: choose a victory card to put onto your deck."),
+                min: 1,
+                max: 1,
+                card_type: Some(CardTypes::Victory)
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            if let Some(index) = picks.first() {
+                bureaucrat_topdeck(state, target, *index);
+            }
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Helper: reveal a card from the player's hand, and put it onto their deck.
+ */
+fn bureaucrat_topdeck(state: &mut GameState, seat: usize, index: usize) {
+    let card = state.players[seat].hand.remove(index);
+    state.reveal_card(seat, card);
+    state.players[seat].deck.push_front(card);
+}
+
+/**
+ * Poacher: discard a card per empty supply pile.
+ */
+fn poacher(state: &mut GameState, frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            let to_discard = state.board.count_empty_piles().min(state.players[seat].hand.len());
+            if to_discard == 0 {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: format!("Poacher: discard {} cards, one per empty supply pile.", to_discard),
+                min: to_discard,
+                max: to_discard,
+                card_type: None
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            discard_picks(state, seat, picks);
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Throne Room: you may play an action card from your hand twice.
+ * The card is played (effect and all) once, then played again, before Throne Room is finished.
+ */
+fn throne_room(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            if !state.players[seat].hand.iter().any(|c| c.get_card_types().contains(&CardTypes::Action)) {
+                return EffectStep::Done;
+            }
+            return ask(frame, DecisionRequest::ChooseCardsFromHand {
+                prompt: String::from("Throne Room: you may choose an action card to play twice."),
+                min: 0,
+                max: 1,
+                card_type: Some(CardTypes::Action)
+            });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            let index = match picks.first() {
+                Some(i) => *i,
+                None => return EffectStep::Done
+            };
+
+            // Play it the first time, remembering where it sits in play for the second time.
+            let card = state.players[seat].hand.remove(index);
+            frame.value = state.players[seat].in_play.len() as i32;
+            frame.stage = 2;
+            match play_for_free(state, seat, card) {
+                Some(effect) => EffectStep::Then(frame, effect),
+                None => EffectStep::Continue(frame)
+            }
+        }
+        (2, _) => {
+            // The card is already in play, so just give the player its properties and effect again.
+            let card = match state.players[seat].in_play.get(frame.value as usize) {
+                Some(card) => *card,
+                None => return EffectStep::Done
+            };
+            let props = card.get_action_properties();
+            state.emit(GameEvent::CardPlayed { seat: seat, card: card });
+            state.players[seat].apply_action_properties(&props);
+            if props.event == ActionEvents::No {
+                return EffectStep::Done;
+            }
+            return EffectStep::Continue(EffectFrame::new(props.event, seat));
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Council Room: each other player draws a card.
+ */
+fn council_room(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    let player_count = state.players.len();
+    for offset in 1..player_count {
+        state.draw_cards((frame.seat + offset) % player_count, 1);
+    }
+    return EffectStep::Done;
+}
+
+/**
+ * Library: draw until you have 7 cards in hand, skipping any action cards you choose to.
+ * Skipped cards are set aside, and discarded once you're done drawing.
+ */
+fn library(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+
+    // Coming back from asking about an action card (the last card set aside): keep it aside, or take it.
+    if frame.stage == 1 {
+        if answer != Some(Decision::YesNo(true)) {
+            if let Some(card) = frame.revealed.pop() {
+                state.players[seat].hand.push(card);
+            }
+        }
+        frame.stage = 0;
+    }
+
+    while state.players[seat].hand.len() < 7 {
+        let card = match state.players[seat].take_from_deck_top() {
+            Some(c) => c,
+            None => break
+        };
+
+        if card.get_card_types().contains(&CardTypes::Action) {
+            let prompt = format!("Library: set aside the {} you drew?", card.get_name());
+            frame.revealed.push(card);
+            return ask(frame, DecisionRequest::YesNo { prompt: prompt });
+        }
+        state.players[seat].hand.push(card);
+    }
+
+    for card in frame.revealed {
+        state.discard_card(seat, card);
+    }
+    return EffectStep::Done;
+}
+
+/**
+ * Sentry: look at the top 2 cards of your deck. Trash and/or discard any number of them.
+ * Put the rest back on top in any order.
+ */
+fn sentry(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            for _ in 0..2 {
+                if let Some(card) = state.players[seat].take_from_deck_top() {
+                    frame.revealed.push(card);
+                }
+            }
+            if frame.revealed.is_empty() {
+                return EffectStep::Done;
+            }
+            let cards = frame.revealed.iter().map(|c| c.get_name().clone()).collect();
+            let max = frame.revealed.len();
+            return ask(frame, DecisionRequest::ChooseCards { prompt: String::from("Sentry: choose any cards to trash."), cards: cards, min: 0, max: max });
+        }
+        (1, Some(Decision::Cards(picks))) => {
+            for card in take_picked(&mut frame.revealed, picks) {
+                state.trash_card(seat, card);
+            }
+            if frame.revealed.is_empty() {
+                return EffectStep::Done;
+            }
+            let cards = frame.revealed.iter().map(|c| c.get_name().clone()).collect();
+            let max = frame.revealed.len();
+            return ask(frame, DecisionRequest::ChooseCards { prompt: String::from("Sentry: choose any cards to discard."), cards: cards, min: 0, max: max });
+        }
+        (2, Some(Decision::Cards(picks))) => {
+            for card in take_picked(&mut frame.revealed, picks) {
+                state.discard_card(seat, card);
+            }
+            if frame.revealed.len() < 2 {
+                sentry_put_back(&mut state.players[seat], frame.revealed, None);
+                return EffectStep::Done;
+            }
+            let cards = frame.revealed.iter().map(|c| c.get_name().clone()).collect();
+            return ask(frame, DecisionRequest::OrderCards { prompt: String::from("Sentry: put the rest back on your deck, top card first."), cards: cards });
+        }
+        (3, Some(Decision::Order(order))) => {
+            sentry_put_back(&mut state.players[seat], frame.revealed, Some(order));
+            return EffectStep::Done;
+        }
+        _ => {
+            // Nothing sensible came back, so put everything back in the order it was drawn.
+            sentry_put_back(&mut state.players[seat], frame.revealed, None);
+            return EffectStep::Done;
+        }
+    }
+}
+
+/**
+ * Helper: take the picked cards out of a list, leaving the rest in their order.
+ */
+fn take_picked(cards: &mut Vec<CardId>, mut picks: Vec<usize>) -> Vec<CardId> {
+    let mut taken = Vec::new();
+    picks.sort();
+    for index in picks.into_iter().rev() {
+        if index < cards.len() {
+            taken.push(cards.remove(index));
+        }
+    }
+    return taken;
+}
+
+/**
+ * Helper: put Sentry's cards back on the deck in the given order (first being the top), or the order they were drawn in.
+ */
+fn sentry_put_back(player: &mut Player, cards: Vec<CardId>, order: Option<Vec<usize>>) {
+    let order = order.unwrap_or((0..cards.len()).collect());
+    let mut cards: Vec<Option<CardId>> = cards.into_iter().map(Some).collect();
+
+    // Push the bottom card first, so the first card in the order ends up on top.
+    for index in order.into_iter().rev() {
+        if let Some(card) = cards.get_mut(index).and_then(|c| c.take()) {
+            player.deck.push_front(card);
+        }
+    }
+}
+
+/**
+ * Artisan: gain a card to your hand costing up to 5. Put a card from your hand onto your deck.
+ */
+fn artisan(state: &mut GameState, mut frame: EffectFrame, answer: Option<Decision>) -> EffectStep {
+    let seat = frame.seat;
+    match (frame.stage, answer) {
+        (0, _) => {
+            let choices = state.board.get_supply_choices(5, None);
+            if !choices.is_empty() {
+                return ask(frame, DecisionRequest::ChooseSupplyPile {
+                    prompt: String::from("Artisan: gain a card to your hand costing up to 5."),
+                    max_cost: 5,
+                    choices: choices,
+                    optional: false
+                });
+            }
+            // Nothing to gain, but a card still goes back on the deck.
+            frame.stage = 1;
+            return artisan_topdeck(state, frame);
+        }
+        (1, answer) => {
+            if let Some(Decision::SupplyPile(Some(pick))) = answer {
+                gain_from_choices(state, seat, 5, None, pick, GainDestination::Hand);
+            }
+            return artisan_topdeck(state, frame);
+        }
+        (2, Some(Decision::Cards(picks))) => {
+            if let Some(index) = picks.first() {
+                let card = state.players[seat].hand.remove(*index);
+                state.players[seat].deck.push_front(card);
+            }
+            return EffectStep::Done;
+        }
+        _ => EffectStep::Done
+    }
+}
+
+/**
+ * Helper: ask which card Artisan puts back onto the deck.
+ */
+fn artisan_topdeck(state: &mut GameState, frame: EffectFrame) -> EffectStep {
+    if state.players[frame.seat].hand.is_empty() {
+        return EffectStep::Done;
+    }
+    return ask(frame, DecisionRequest::ChooseCardsFromHand {
+        prompt: String::from("Artisan: put a card from your hand onto your deck."),
+        min: 1,
+        max: 1,
+        card_type: None
+    });
+}
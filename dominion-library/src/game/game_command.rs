@@ -0,0 +1,33 @@
+/*
+SPUStudnet
+12/15/2024
+game_command.rs
+Defines the moves a player can make, as plain values.
+Every move goes through GameManager::apply, which checks it's allowed before making it.
+Being plain data, commands can be saved, sent over a network, or replayed.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::board::CardSet;
+use crate::player::agent::Decision;
+
+/**
+ * GameCommand
+ * One move by one player.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameCommand {
+    // Play the card at this index of your hand (an action in your action phase, or a treasure in your buy phase).
+    PlayCard { hand_index: usize },
+    // Play every treasure in your hand. Only in your buy phase.
+    PlayAllTreasures,
+    // Buy a card from the supply pile at this index of the set. Only in your buy phase.
+    Buy { set: CardSet, index: usize },
+    // Move on to your next phase. Leaving your last phase ends your turn.
+    EndPhase,
+    // End your turn, skipping whatever's left of it.
+    EndTurn,
+    // Answer the decision the game is waiting on you for.
+    ResolveDecision(Decision)
+}
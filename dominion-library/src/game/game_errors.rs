@@ -57,6 +57,101 @@ impl fmt::Display for InvalidActionError {
     }
 }
 
+/**
+ * InvalidSetupError
+ * Emitted when a game can't be created with the options it was given.
+ * (Too many or too few players, etc.)
+ */
+#[derive(Debug)]
+pub struct InvalidSetupError {
+    pub reason: String
+}
+
+impl Error for InvalidSetupError {}
+
+impl fmt::Display for InvalidSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid game setup: {}", self.reason)
+    }
+}
+
+/**
+ * UnsatisfiableKingdomError
+ * Emitted when no kingdom can be picked that meets every randomizer constraint.
+ * Lists each reason the constraints can't be met.
+ */
+#[derive(Debug)]
+pub struct UnsatisfiableKingdomError {
+    pub reasons: Vec<String>
+}
+
+impl Error for UnsatisfiableKingdomError {}
+
+impl fmt::Display for UnsatisfiableKingdomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The kingdom constraints can't be met: {}", self.reasons.join(" "))
+    }
+}
+
+/**
+ * CardDataError
+ * Emitted when card definitions can't be loaded.
+ * Says where the cards came from, and lists every problem found with them.
+ */
+#[derive(Debug)]
+pub struct CardDataError {
+    pub source: String,
+    pub problems: Vec<String>
+}
+
+impl Error for CardDataError {}
+
+impl fmt::Display for CardDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Couldn't load cards from {}:", self.source)?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        return Ok(());
+    }
+}
+
+/**
+ * SaveFileError
+ * Emitted when a game can't be saved, or a save can't be loaded.
+ * Says which file (if any), and what went wrong with it.
+ */
+#[derive(Debug)]
+pub struct SaveFileError {
+    pub source: String,
+    pub reason: String
+}
+
+impl Error for SaveFileError {}
+
+impl fmt::Display for SaveFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Couldn't use the save {}: {}", self.source, self.reason)
+    }
+}
+
+/**
+ * ReplayError
+ * Emitted when a replay can't be read, or doesn't play out the way it was recorded.
+ */
+#[derive(Debug)]
+pub struct ReplayError {
+    pub reason: String
+}
+
+impl Error for ReplayError {}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The replay doesn't match: {}", self.reason)
+    }
+}
+
 /**
  * Enum for encapsulation of errors, so the return can be generic.
  */
@@ -64,7 +159,11 @@ impl fmt::Display for InvalidActionError {
 pub enum GameErrors {
     OutOfCardsError(OutOfCardsError),
     CardNotFoundError(CardNotFoundError),
-    InvalidActionError(InvalidActionError)
+    InvalidActionError(InvalidActionError),
+    InvalidSetupError(InvalidSetupError),
+    UnsatisfiableKingdomError(UnsatisfiableKingdomError),
+    SaveFileError(SaveFileError),
+    ReplayError(ReplayError)
 }
 
 /**
@@ -87,3 +186,27 @@ impl From<InvalidActionError> for GameErrors {
         Self::InvalidActionError(value)
     }
 }
+
+impl From<InvalidSetupError> for GameErrors {
+    fn from(value: InvalidSetupError) -> Self {
+        Self::InvalidSetupError(value)
+    }
+}
+
+impl From<UnsatisfiableKingdomError> for GameErrors {
+    fn from(value: UnsatisfiableKingdomError) -> Self {
+        Self::UnsatisfiableKingdomError(value)
+    }
+}
+
+impl From<SaveFileError> for GameErrors {
+    fn from(value: SaveFileError) -> Self {
+        Self::SaveFileError(value)
+    }
+}
+
+impl From<ReplayError> for GameErrors {
+    fn from(value: ReplayError) -> Self {
+        Self::ReplayError(value)
+    }
+}
\ No newline at end of file
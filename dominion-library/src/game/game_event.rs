@@ -0,0 +1,91 @@
+/*
+SPUStudnet
+12/15/2024
+game_event.rs
+Defines the events a game emits as it's played, and the trait for anything that wants to follow them.
+The game log is one subscriber to these; frontends, statistics and replays can be others.
+*/
+
+use crate::card_manager::card_registry::CardId;
+use crate::game::game_results::GameResult;
+use crate::player::phases::PlayerPhases;
+
+/**
+ * DrawnCards
+ * The cards a player drew.
+ * The game always knows which cards they were (Visible), but only the player drawing them gets to see them,
+ * so everyone else should be shown how many were drawn (Hidden). See GameEvent::seen_by.
+ */
+#[derive(Clone, Debug)]
+pub enum DrawnCards {
+    Visible(Vec<CardId>),
+    Hidden(usize)
+}
+
+/**
+ * GameEvent
+ * Something that happened in the game, in the order it happened.
+ * Players are given by seat, and cards by id.
+ * Every GameCommand applied returns the events it caused, and every subscriber is sent them as well.
+ */
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    // The kingdom was picked at the start of the game (where from, and which cards).
+    KingdomChosen { source: String, cards: Vec<CardId> },
+    TurnStarted { seat: usize, turn: u32 },
+    PhaseChanged { seat: usize, from: PlayerPhases, to: PlayerPhases },
+    CardPlayed { seat: usize, card: CardId },
+    CardBought { seat: usize, card: CardId },
+    // Gained without being bought (from a card's effect).
+    CardGained { seat: usize, card: CardId },
+    CardTrashed { seat: usize, card: CardId },
+    // Discarded outside of cleanup (from a card's effect).
+    CardDiscarded { seat: usize, card: CardId },
+    CardRevealed { seat: usize, card: CardId },
+    // The player shuffled their discard to make a new deck (or shuffled their starting deck).
+    Shuffled { seat: usize },
+    Drew { seat: usize, cards: DrawnCards },
+    TurnEnded { seat: usize },
+    GameEnded(GameResult),
+    // The seat took back its last move, or made a move it took back again (see GameManager::undo).
+    // The game is back how it was, so anything following it should look again rather than work out what changed.
+    MoveUndone { seat: usize },
+    MoveRedone { seat: usize }
+}
+
+/**
+ * GameEvent implementation
+ */
+impl GameEvent {
+    /**
+     * seen_by
+     * The event as the player in the given seat gets to see it: other players' draws are hidden, everything else is public.
+     */
+    pub fn seen_by(&self, viewer: usize) -> GameEvent {
+        match self {
+            GameEvent::Drew { seat, cards: DrawnCards::Visible(cards) } if *seat != viewer => {
+                GameEvent::Drew { seat: *seat, cards: DrawnCards::Hidden(cards.len()) }
+            }
+            _ => self.clone()
+        }
+    }
+}
+
+/**
+ * EventSubscriber trait
+ * Anything that wants to follow a game as it's played (register it with GameManager::subscribe).
+ * Subscribers are sent every event after each move, in order, and only see events from when they were registered on.
+ * Like agents, they have to be Send and Sync, as the game holding them can be moved to other threads.
+ */
+pub trait EventSubscriber: Send + Sync {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+/**
+ * Any closure taking an event can subscribe, for when a whole struct is more than is needed.
+ */
+impl<F: FnMut(&GameEvent) + Send + Sync> EventSubscriber for F {
+    fn on_event(&mut self, event: &GameEvent) {
+        self(event);
+    }
+}
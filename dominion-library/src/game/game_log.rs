@@ -0,0 +1,119 @@
+/*
+SPUStudnet
+12/15/2024
+game_log.rs
+Defines the game log: the human-readable history of a game, rendered from its events.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::card_manager::card::Card;
+use crate::game::game_event::{EventSubscriber, GameEvent};
+
+/**
+ * GameLog struct
+ * Contains a game log for all actions in the game.
+ * It's written by turning each of the game's events into a line of text, so it's just one way of showing the events.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct GameLog {
+    game_log: Vec<String>,
+    // The name of the player in each seat, as events only give the seat.
+    names: Vec<String>,
+    // Whether messages are being written at all.
+    // Simulations playing huge numbers of games turn this off, as nobody reads their logs.
+    recording: bool
+}
+
+/**
+ * GameLog implementation
+ */
+impl GameLog {
+    /**
+     * Create a new game log, for the players with the given names (in seat order).
+     */
+    pub(crate) fn new(names: Vec<String>) -> GameLog {
+        let mut log = GameLog {
+            game_log: Vec::new(),
+            names: names,
+            recording: true
+        };
+
+        log.record(String::from("Game start!"));
+
+        return log;
+    }
+
+    /**
+     * render
+     * The line of the log for an event, if it gets one.
+     * Draws, shuffles and phase changes happen too often to be worth reading about, so they're left out.
+     */
+    fn render(&self, event: &GameEvent) -> Option<String> {
+        match event {
+            GameEvent::KingdomChosen { source, cards } => {
+                let names: Vec<String> = cards.iter().map(|c| c.get_name().clone()).collect();
+                Some(format!("Kingdom ({}): {}", source, names.join(", ")))
+            }
+            GameEvent::CardPlayed { seat, card } => Some(format!("{} played {}", self.names[*seat], card.get_name())),
+            GameEvent::CardBought { seat, card } => Some(format!("{} bought {}", self.names[*seat], card.get_name())),
+            GameEvent::CardGained { seat, card } => Some(format!("{} gained {}", self.names[*seat], card.get_name())),
+            GameEvent::CardTrashed { seat, card } => Some(format!("{} trashed {}", self.names[*seat], card.get_name())),
+            GameEvent::CardDiscarded { seat, card } => Some(format!("{} discarded {}", self.names[*seat], card.get_name())),
+            GameEvent::CardRevealed { seat, card } => Some(format!("{} revealed {}", self.names[*seat], card.get_name())),
+            GameEvent::GameEnded(_) => Some(String::from("Game over!")),
+            _ => None
+        }
+    }
+
+    /**
+     * set_recording
+     * Turn writing to the log on or off.
+     */
+    pub(crate) fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /**
+     * fork
+     * A new, empty log that records (or doesn't) the same as this one.
+     * Used by forked games, which don't need the history leading up to them.
+     */
+    pub(crate) fn fork(&self) -> GameLog {
+        return GameLog {
+            game_log: Vec::new(),
+            names: self.names.clone(),
+            recording: self.recording
+        };
+    }
+
+    /**
+     * record
+     * Write a message to the log.
+     */
+    fn record(&mut self, message: String) {
+        self.game_log.push(message);
+    }
+
+    /**
+     * Get log
+     * Lazy implementation, if needed to get the game's entire log.
+     */
+    pub fn get_log(&self) -> Vec<String> {
+        return self.game_log.clone();
+    }
+}
+
+/**
+ * The log follows the game like any other subscriber.
+ */
+impl EventSubscriber for GameLog {
+    fn on_event(&mut self, event: &GameEvent) {
+        if !self.recording {
+            return;
+        }
+        if let Some(message) = self.render(event) {
+            self.record(message);
+        }
+    }
+}
@@ -9,92 +9,179 @@ However, this provides a reasonable interface for playing a dominion game agains
 */
 
 
-use std::{
-    cell::RefCell, rc::Rc
-};
-
+use rand::Rng;
+use static_assertions::assert_impl_all;
 
+use crate::card_manager::card::Card;
 use crate::game::board::PlayerInterface;
-use crate::player::player::Player;
+use crate::player::player::{PlayerSeat, PlayerStatsInterface};
 use crate::player::phases::PlayerPhases;
-use crate::player::player::PlayerUIInterface;
-use crate::game::board::Board;
-use crate::game::pile_builder::*;
+use crate::player::seat::{Seat, SeatKind};
 use crate::game::board::CardSet;
-use crate::game::player_middleware::PlayerMiddleware;
+use crate::game::game_state::GameState;
+use crate::game::game_command::GameCommand;
+use crate::game::game_results::GameResult;
+use crate::game::replay::{RecordedMove, Replay, REPLAY_VERSION};
+use crate::game::save_file;
+use crate::game::kingdom::KingdomChoice;
+use crate::game::game_event::{EventSubscriber, GameEvent};
+use crate::game::turn_controller::TurnController;
+use crate::game::undo::{self, UndoStack};
+use crate::game::card_effects::{self, EffectFrame, EffectStep};
+use crate::card_manager::card_properties::{ActionEvents, CardTypes};
+use crate::player::agent::{Agent, BasicBotAgent, Decision, DecisionView, InteractiveAgent, PendingDecision};
+use crate::player::strategy::{Strategy, StrategyView};
 
-use super::game_errors::GameErrors;
+use super::game_errors::{CardNotFoundError, GameErrors, InvalidActionError, InvalidSetupError};
 
 /**
  * GameManager struct
  * Contains all information about the game.
- * Holds control of the board, two players, and the middleware.
+ * Holds the game state (every player, the board, the log and the turn order, see game_state.rs),
+ * the agent making decisions for each seat, the strategy each bot plays its turns with,
+ * everything following the game's events, and the moves that can be undone (see undo.rs).
  */
 pub struct GameManager {
-    player1: Player,
-    player2: Player,
-    board: Rc<RefCell<Board>>,
-    player_middleware: Rc<RefCell<PlayerMiddleware>>
+    state: GameState,
+    agents: Vec<Box<dyn Agent>>,
+    strategies: Vec<Box<dyn Strategy>>,
+    // Decisions agents have answered during the move being made, waiting for execute to record them.
+    answers: Vec<RecordedMove>,
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+    undo: UndoStack
 }
 
+// A whole game can be handed to another thread (to let bots think in the background, simulate games in parallel, or serve many games),
+// and shared between threads behind a lock.
+assert_impl_all!(GameManager: Send, Sync);
+
 impl GameManager {
     /**
      * New method.
-     * Builds out all the information needed to create a brand-new game.
+     * Builds out all the information needed to create a brand-new game, with a random kingdom.
+     * Seats are given in turn order, and the first seat takes the first turn. Two to six seats are supported.
+     * If a seed is given, every shuffle in the game is derived from it, so the same seed and the same inputs
+     * always produce the same hands. If no seed is given, one is picked at random (and can be read back with get_seed).
      */
-    pub fn new() -> GameManager {
-
-        // Create the board from the given piles, using helper functions.
-        let mut board = Board {
-            victory_cards: build_victory_piles(),
-            treasure_cards: build_treasure_piles(),
-            kingdom_set: Vec::new(),
-            trash: Vec::new()
-            
+    pub fn new(seats: Vec<Seat>, seed: Option<u64>) -> Result<GameManager, GameErrors> {
+        return GameManager::with_kingdom(seats, seed, KingdomChoice::Random);
+    }
+
+    /**
+     * with_kingdom
+     * The same as new, but with the kingdom picked the given way: at random, from a recommended set, or from a list of cards.
+     * A random kingdom is picked using the seed, so the same seed gives the same kingdom as well as the same hands.
+     */
+    pub fn with_kingdom(seats: Vec<Seat>, seed: Option<u64>, kingdom: KingdomChoice) -> Result<GameManager, GameErrors> {
+        return GameManager::set_up(seats, seed, kingdom, true);
+    }
+
+    /**
+     * simulate
+     * Play a whole game between bots, with the log turned off, and return the finished game to read the results from.
+     * Meant for strategy research, where huge numbers of games are played and nobody reads their logs.
+     * Returns an error if any seat isn't a bot, as the game would stop to wait on them.
+     */
+    pub fn simulate(seats: Vec<Seat>, seed: Option<u64>, kingdom: KingdomChoice) -> Result<GameManager, GameErrors> {
+        if seats.iter().any(|s| s.kind != SeatKind::Bot) {
+            return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: String::from("Only games between bots can be simulated.")}));
+        }
+        return GameManager::set_up(seats, seed, kingdom, false);
+    }
+
+    /**
+     * set_up
+     * Builds the game for new, with_kingdom and simulate, then starts the first turn.
+     */
+    fn set_up(seats: Vec<Seat>, seed: Option<u64>, kingdom: KingdomChoice, logging: bool) -> Result<GameManager, GameErrors> {
+        if seats.len() < 2 || seats.len() > 6 {
+            return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("Dominion is played by 2 to 6 players, but {} seats were given.", seats.len())}));
+        }
+
+        // Pick the seed, and set up the game from it.
+        let seed = match seed {
+            Some(x) => x,
+            None => rand::thread_rng().gen()
         };
-        board.kingdom_set.push(build_moat_pile());
-        board.kingdom_set.push(build_woodcutter_pile());
-        board.kingdom_set.push(build_village_pile());
-        board.kingdom_set.push(build_smithy_pile());
-        board.kingdom_set.push(build_market_pile());
-
-        // Create the middleware (Currently unused)
-        let middle_cell = Rc::new(RefCell::new(PlayerMiddleware::new()));
-        
-        // Create the Boards refCell
-        // Sidenote: This needs to be a refcell as it is shared between three objects, and each need immutable references, although never at the same time.
-        let board_ref = Rc::new(RefCell::new(board));
-
-        // Create player 1.
-        let mut player1 = Player::new(build_default_player_deck(), board_ref.clone(), middle_cell.clone());
-        player1.name = String::from("Player");
-
-        // Create player 2.
-        let mut player2 = Player::new(build_default_player_deck(), board_ref.clone(), middle_cell.clone());
-        player2.name = String::from("CPU");
-
-        // Make the GameManager we are going to return from all the above properties.
+        let state = GameState::new(&seats, seed, &kingdom)?;
+
         let mut gm = GameManager {
-            player1: player1,
-            player2: player2,
-            board: board_ref.clone(),
-            player_middleware: middle_cell.clone()
+            agents: seats.iter().map(|s| GameManager::default_agent(s.kind)).collect(),
+            strategies: seats.iter().map(|s| s.strategy.build()).collect(),
+            answers: Vec::new(),
+            subscribers: Vec::new(),
+            undo: UndoStack::new(),
+            state: state
         };
+        gm.set_logging(logging);
 
-        // Shuffle the decks of both players.
-        gm.player1.shuffle_deck();
-        gm.player2.shuffle_deck();
+        // Start as the first seat's turn.
+        // If the first seats are bots, they play straight away, so the game is always waiting on a human (or over) after this.
+        for event in gm.state.turn_controller.start_turn(&mut gm.state.players) {
+            gm.state.emit(event);
+        }
+        gm.play_bot_turns();
 
-        // Tell them to cleanup and draw their hand.
-        gm.player1.cleanup_and_draw();
-        gm.player2.cleanup_and_draw();
+        return Ok(gm);
+    }
 
-        // Start as player 1's turn.
-        gm.player1.phase = PlayerPhases::Action;
+    /**
+     * from_state
+     * Run a game from a state it's already in (like a fork of another game).
+     * Every seat gets the default agent for its kind, and bots play the strategy their seat was set up with.
+     * If it's a bot's turn, the bots play until it isn't.
+     */
+    pub fn from_state(state: GameState) -> GameManager {
+        let mut gm = GameManager {
+            agents: state.players.iter().map(|p| GameManager::default_agent(p.kind)).collect(),
+            strategies: state.players.iter().map(|p| p.strategy.build()).collect(),
+            answers: Vec::new(),
+            subscribers: Vec::new(),
+            undo: UndoStack::new(),
+            state: state
+        };
+        gm.play_bot_turns();
+        return gm;
+    }
 
+    /**
+     * fork
+     * An independent copy of this game, to look ahead on (see GameState::fork).
+     * Agents can't be copied, so any seat given its own agent with set_agent gets the default agent for its kind in the fork
+     * (and the same goes for strategies given with set_strategy).
+     * Subscribers aren't copied either, so nothing following this game hears about what happens in the fork.
+     */
+    pub fn fork(&self, fork_seed: u64) -> GameManager {
+        return GameManager::from_state(self.state.fork(fork_seed));
+    }
 
+    /**
+     * load
+     * Pick a saved game back up from a save file (see save_file.rs), exactly where it was saved.
+     * Every seat gets the default agent for its kind, as agents aren't saved.
+     */
+    pub fn load(path: &str) -> Result<GameManager, GameErrors> {
+        return Ok(GameManager::from_state(save_file::load_from_file(path)?));
+    }
 
-        return gm;
+    /**
+     * save
+     * Save the game to a file, so it can be loaded and carried on with later.
+     */
+    pub fn save(&self, path: &str) -> Result<(), GameErrors> {
+        return save_file::save_to_file(&self.state, path);
+    }
+
+    /**
+     * default_agent
+     * The agent a seat gets unless it's given another.
+     * Humans answer through the frontend, so their agent defers every decision to it.
+     */
+    fn default_agent(kind: SeatKind) -> Box<dyn Agent> {
+        match kind {
+            SeatKind::Human => Box::new(InteractiveAgent),
+            SeatKind::Bot => Box::new(BasicBotAgent)
+        }
     }
 
     /**
@@ -109,22 +196,22 @@ impl GameManager {
 
 
         // For each pile, get the cost, how many are left, and the name of the pile.
-        for pile in 0..self.board.borrow().kingdom_set.len() {
-            kingdom_data.push((self.board.borrow().kingdom_set.get(pile).unwrap().pile_name.clone(), 
-            self.board.borrow().kingdom_set.get(pile).unwrap().cards_left, 
-            self.board.borrow().get_card_price(pile, CardSet::Kingdoms).expect("Kingdom set asked for a card that didn't exist on creation.")));
+        for pile in 0..self.state.board.kingdom_set.len() {
+            kingdom_data.push((self.state.board.kingdom_set.get(pile).unwrap().get_pile_name(), 
+            self.state.board.kingdom_set.get(pile).unwrap().cards_left, 
+            self.state.board.get_card_price(pile, CardSet::Kingdoms).expect("Kingdom set asked for a card that didn't exist on creation.")));
         }
 
-        for pile in 0..self.board.borrow().victory_cards.len() {
-            victory_data.push((self.board.borrow().victory_cards.get(pile).unwrap().pile_name.clone(), 
-            self.board.borrow().victory_cards.get(pile).unwrap().cards_left,
-            self.board.borrow().get_card_price(pile, CardSet::Victories).expect("Victory set asked for a card that didn't exist on creation").clone()));
+        for pile in 0..self.state.board.victory_cards.len() {
+            victory_data.push((self.state.board.victory_cards.get(pile).unwrap().get_pile_name(), 
+            self.state.board.victory_cards.get(pile).unwrap().cards_left,
+            self.state.board.get_card_price(pile, CardSet::Victories).expect("Victory set asked for a card that didn't exist on creation")));
         }
 
-        for pile in 0..self.board.borrow().treasure_cards.len() {
-            treasure_data.push((self.board.borrow().treasure_cards.get(pile).unwrap().pile_name.clone(), 
-            self.board.borrow().treasure_cards.get(pile).unwrap().cards_left,
-            self.board.borrow().get_card_price(pile, CardSet::Treasures).expect("Treasure set asked for a card that didn't exist on creation").clone()));
+        for pile in 0..self.state.board.treasure_cards.len() {
+            treasure_data.push((self.state.board.treasure_cards.get(pile).unwrap().get_pile_name(), 
+            self.state.board.treasure_cards.get(pile).unwrap().cards_left,
+            self.state.board.get_card_price(pile, CardSet::Treasures).expect("Treasure set asked for a card that didn't exist on creation")));
         }
 
         // Return the newly created piledata struct.
@@ -135,70 +222,481 @@ impl GameManager {
         }
     }
 
-    // Get a mutable reference to player1 (Useful for UI)
-    pub fn get_player_mut_character(&mut self) -> &mut impl PlayerUIInterface {
-        return &mut self.player1;
+    // Get the player in a seat, able to buy and play cards (Useful for UI)
+    // Moves made through it go through apply, so they're checked and recorded like any other.
+    pub fn get_player_mut_character(&mut self, seat: usize) -> PlayerSeat<'_> {
+        return PlayerSeat { game: self, seat: seat };
+    }
+
+    // Get an immutable version of the player in a seat.
+    pub fn get_player_character(&self, seat: usize) -> &impl PlayerStatsInterface {
+        return &self.state.players[seat];
+    }
+
+    // set_logging
+    // Turn the game log on or off. Simulations playing huge numbers of games turn it off, as nobody reads it.
+    pub fn set_logging(&mut self, logging: bool) {
+        self.state.log.set_recording(logging);
+    }
+
+    // How many seats are at the table.
+    pub fn get_player_count(&self) -> usize {
+        return self.state.players.len();
+    }
+
+    // The seat whose turn it is.
+    pub fn get_current_player_index(&self) -> usize {
+        return self.state.turn_controller.get_current_player();
+    }
+
+    // Get the turn controller, to see whose turn it is and how far the game has gone.
+    pub fn get_turn_controller(&self) -> &TurnController {
+        return &self.state.turn_controller;
+    }
+
+    // Is the seat played by a human or a bot?
+    pub fn get_seat_kind(&self, seat: usize) -> SeatKind {
+        return self.state.players[seat].kind;
+    }
+
+    // Get the game's state, to copy or fork it.
+    pub fn get_state(&self) -> &GameState {
+        return &self.state;
     }
 
-    // Get an immutable version to player1
-    pub fn get_player_character(&self) -> &impl PlayerUIInterface {
-        return &self.player1;
+    // Get the seed this game was created with.
+    // Include it in bug reports: GameManager::new(Some(seed)) replays the same shuffles.
+    pub fn get_seed(&self) -> u64 {
+        return self.state.seed;
     }
 
     // Get the update log from the middleware.
     pub fn get_logs(&self) -> Vec<String> {
-        self.player_middleware.borrow_mut().get_log().clone()
+        self.state.log.get_log()
     }
 
-    // Run the player2 ai.
-    // This is effectively "Big money,"
-    // The simplest dominion strategy that has a good chance of winning a game.
-    pub fn player2_basic_ai(&mut self) {
-        // Advance phase until we can play our treasures.
-        while self.player2.phase != PlayerPhases::Buy {
-            self.player2.advance_phase();
+    // get_trash
+    // The names of every card in the trash, oldest first.
+    pub fn get_trash(&self) -> Vec<String> {
+        return self.state.board.get_trash_names();
+    }
+
+    // set_agent
+    // Replace the agent making decisions for a seat (for example, with a ScriptedAgent in a test).
+    pub fn set_agent(&mut self, seat: usize, agent: Box<dyn Agent>) {
+        self.agents[seat] = agent;
+    }
+
+    // set_strategy
+    // Replace the strategy a bot seat plays its turns with (for example, with one of your own).
+    // The built-in strategies can be picked when the game is set up instead, with Seat::bot_playing.
+    pub fn set_strategy(&mut self, seat: usize, strategy: Box<dyn Strategy>) {
+        self.strategies[seat] = strategy;
+    }
+
+    // subscribe
+    // Register something to be sent the game's events (see game_event.rs), from now on.
+    // Events are sent out after every move, in the order they happened, after the log has been written.
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    // publish
+    // Send every event since the last move to the log and every subscriber, and return them.
+    fn publish(&mut self) -> Vec<GameEvent> {
+        let events = self.state.take_events();
+        for event in events.iter() {
+            self.state.log.on_event(event);
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.on_event(event);
+            }
         }
+        return events;
+    }
 
-        // Play all treasures
-        let _ = self.player2.play_treasures();
+    // get_replay
+    // Everything needed to play this game again, move for move (see replay.rs), for bug reports and checking engine changes.
+    // Forks have nothing to replay, as they don't shuffle the way the seed says.
+    pub fn get_replay(&self) -> Option<Replay> {
+        let moves = self.state.history.as_ref()?;
+        let final_scores = self.state.turn_controller.get_result()
+            .map(|_| self.state.players.iter().map(|p| p.get_victory_points()).collect());
+
+        return Some(Replay {
+            version: REPLAY_VERSION,
+            seed: self.state.seed,
+            kingdom: self.state.board.kingdom_set.iter().map(|p| p.card.get_name().clone()).collect(),
+            seats: self.state.players.iter().map(|p| Seat { name: p.name.clone(), kind: p.kind, strategy: p.strategy }).collect(),
+            moves: moves.clone(),
+            final_scores: final_scores
+        });
+    }
+
+    // get_pending_decision
+    // The decision the game is waiting on, if there is one.
+    // Nothing else can happen until it's answered with resolve_decision.
+    pub fn get_pending_decision(&self) -> Option<&PendingDecision> {
+        return self.state.pending_decision.as_ref();
+    }
 
-        // If our buying power is greater than or equal to eight, buy a province.
-        if self.player2.get_buying_power() >= 8 {
-            self.player2.buy_card(2, CardSet::Victories);
+    // apply
+    // Make a move for the player in the given seat, after checking it's theirs to make: it has to be their turn,
+    // and if the game is waiting on a decision, the only move anyone can make is the answer (by the seat it's waiting on).
+    // Returns everything that happened because of the move, including any bot turns played after it.
+    // Every move goes through here, so anything built on moves (like replays, or playing over a network) only needs this.
+    pub fn apply(&mut self, player: usize, command: GameCommand) -> Result<Vec<GameEvent>, GameErrors> {
+        self.check_move_allowed(player, &command)?;
+        let before = self.state.clone();
+        self.execute(player, command)?;
+        let events = self.play_bot_turns();
+
+        let revealed = undo::reveals_information(player, &before, &self.state, &events);
+        self.undo.record(player, before, revealed);
+        return Ok(events);
+    }
+
+    // undo
+    // Take back the seat's last move, if it didn't reveal anything: it can't be undone past the last shuffle, draw or reveal,
+    // the start of the turn, or a decision made by someone else (the undo barrier).
+    // Returns the MoveUndone event, once it's been sent out. The log and the move history go back too.
+    pub fn undo(&mut self, player: usize) -> Result<Vec<GameEvent>, GameErrors> {
+        self.undo.undo(player, &mut self.state)?;
+        self.state.emit(GameEvent::MoveUndone { seat: player });
+        return Ok(self.publish());
+    }
+
+    // redo
+    // Make the seat's last undone move again. Making any other move means nothing undone can be redone.
+    pub fn redo(&mut self, player: usize) -> Result<Vec<GameEvent>, GameErrors> {
+        self.undo.redo(player, &mut self.state)?;
+        self.state.emit(GameEvent::MoveRedone { seat: player });
+        return Ok(self.publish());
+    }
+
+    // undo_depth
+    // How many of the seat's moves can be undone before reaching the undo barrier (none if it's right behind them).
+    pub fn undo_depth(&self, player: usize) -> usize {
+        return self.undo.undo_depth(player);
+    }
+
+    // redo_depth
+    // How many of the seat's undone moves can be made again.
+    pub fn redo_depth(&self, player: usize) -> usize {
+        return self.undo.redo_depth(player);
+    }
+
+    // legal_moves
+    // Every move the player in the given seat can make right now, each one a command apply would accept.
+    // While a decision is pending, that's every valid answer to it (if it's theirs to make), and nothing else.
+    // Otherwise, on their turn, it's every card in their hand they can play, every supply pile they can afford,
+    // playing all their treasures (if they have any to play), and moving on.
+    pub fn legal_moves(&self, player: usize) -> Vec<GameCommand> {
+        let mut moves = Vec::new();
+        if player >= self.state.players.len() || self.state.turn_controller.is_game_over() {
+            return moves;
+        }
+
+        let p = &self.state.players[player];
+        if let Some(pending) = &self.state.pending_decision {
+            if pending.seat == player {
+                for decision in pending.request.valid_decisions(&p.hand) {
+                    moves.push(GameCommand::ResolveDecision(decision));
+                }
+            }
+            return moves;
         }
-        else if self.player2.get_buying_power() >= 6 {
-            // If the buying power is greater than or equal to six, and the victories are unaffordable, buy a gold.
-            self.player2.buy_card(2, CardSet::Treasures);
+        if player != self.state.turn_controller.get_current_player() {
+            return moves;
         }
-        else if self.player2.get_buying_power() >= 3 {
-            // Otherwise, buy a sliver,
-            self.player2.buy_card(1, CardSet::Treasures);
+
+        // Cards that can be played in this phase. Actions also need an action left to play them with.
+        for (index, card) in p.hand.iter().enumerate() {
+            let is_treasure = card.get_card_types().contains(&CardTypes::Treasure);
+            let needs_action = !is_treasure && card.get_card_types().contains(&CardTypes::Action);
+            if card.get_playing_phase() == &p.phase && !(needs_action && p.actions_remaining == 0) {
+                moves.push(GameCommand::PlayCard { hand_index: index });
+            }
+        }
+
+        if p.phase == PlayerPhases::Buy {
+            if p.hand.iter().any(|c| c.get_card_types().contains(&CardTypes::Treasure)) {
+                moves.push(GameCommand::PlayAllTreasures);
+            }
+
+            // Every pile with cards left that the player has the money for.
+            if p.buys > 0 {
+                for choice in self.state.board.get_supply_choices(p.buy_power as i32, None) {
+                    moves.push(GameCommand::Buy { set: choice.set, index: choice.index });
+                }
+            }
         }
 
-        // Cleanup, and advance to idle.
-        self.player2.cleanup_and_draw();
+        if matches!(p.phase, PlayerPhases::Action | PlayerPhases::Buy | PlayerPhases::Night) {
+            moves.push(GameCommand::EndPhase);
+        }
+        moves.push(GameCommand::EndTurn);
 
-        self.player1.advance_phase();
+        return moves;
     }
 
-    // check_ending
-    // At the end of every turn,this should be called.
-    // If the game has ended, it returns a vector of scores with hardcoded names.
-    // If the game hasn't ended, it returns nothing.
-    pub fn check_ending(&mut self) -> Option<Vec<(String, i32)>> {
-        if self.board.borrow().check_ending() == true {
-            let p1_vp = self.player1.get_victory_points();
-            let p2_vp = self.player2.get_victory_points();
+    // check_move_allowed
+    // Returns an error if the given seat can't make the given move right now.
+    fn check_move_allowed(&self, player: usize, command: &GameCommand) -> Result<(), GameErrors> {
+        if player >= self.state.players.len() {
+            return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: format!("There is no seat {}.", player)}));
+        }
+        if self.state.turn_controller.is_game_over() {
+            return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("The game is over.")}));
+        }
 
-            return Some(vec![(String::from("Player "), p1_vp), (String::from("CPU "), p2_vp)]);
+        match (&self.state.pending_decision, command) {
+            (Some(pending), GameCommand::ResolveDecision(_)) => {
+                if pending.seat != player {
+                    return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("The game is waiting on another player's decision.")}));
+                }
+            }
+            (None, GameCommand::ResolveDecision(_)) => {
+                return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("There is no decision to make right now.")}));
+            }
+            (Some(_), _) => {
+                return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("A decision has to be made first.")}));
+            }
+            (None, _) => {
+                if player != self.state.turn_controller.get_current_player() {
+                    return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("It isn't that player's turn.")}));
+                }
+            }
         }
-        else {
-            None
+        return Ok(());
+    }
+
+    // execute
+    // Make a move that's already been checked with check_move_allowed, and add it to the game's history once it's been made.
+    // Bots use this directly, as they only make their own moves.
+    // This is the only place moves are recorded, so a move that turns out not to be allowed never makes it into a replay.
+    fn execute(&mut self, seat: usize, command: GameCommand) -> Result<(), GameErrors> {
+        let result = self.make_move(seat, command.clone());
+        let answers = std::mem::take(&mut self.answers);
+        if result.is_ok() {
+            // Decisions agents answered while the move was being made come after it.
+            if let Some(history) = &mut self.state.history {
+                history.push(RecordedMove { seat: seat, command: command });
+                history.extend(answers);
+            }
         }
+        return result;
+    }
+
+    // make_move
+    // The rules for each move. Only execute calls this.
+    fn make_move(&mut self, seat: usize, command: GameCommand) -> Result<(), GameErrors> {
+        match command {
+            GameCommand::PlayCard { hand_index } => {
+                if hand_index >= self.state.players[seat].hand.len() {
+                    return Err(GameErrors::CardNotFoundError(CardNotFoundError {index: hand_index}));
+                }
+                if let Some(e) = self.state.players[seat].play_card(hand_index) {
+                    return Err(e);
+                }
+
+                // The card we just played is the last one put into play. Resolve its effect, if it has one.
+                let effect = match self.state.players[seat].in_play.last() {
+                    Some(card) => card.get_action_properties().event,
+                    None => ActionEvents::No
+                };
+                if effect != ActionEvents::No {
+                    self.state.effects.push(EffectFrame::new(effect, seat));
+                    self.run_effects();
+                }
+                return Ok(());
+            }
+            GameCommand::PlayAllTreasures => {
+                if self.state.players[seat].phase != PlayerPhases::Buy {
+                    return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("You can only play treasures during your buy phase.")}));
+                }
+                if !self.state.players[seat].hand.iter().any(|c| c.get_card_types().contains(&CardTypes::Treasure)) {
+                    return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("You have no treasures to play.")}));
+                }
+                self.state.players[seat].play_treasures();
+                return Ok(());
+            }
+            GameCommand::Buy { set, index } => {
+                if let Some(e) = self.state.buy_card(seat, index, set) {
+                    return Err(e);
+                }
+                return Ok(());
+            }
+            GameCommand::EndPhase => {
+                for event in self.state.turn_controller.advance_phase(&mut self.state.players, &self.state.board)? {
+                    self.state.emit(event);
+                }
+                return Ok(());
+            }
+            GameCommand::EndTurn => {
+                for event in self.state.turn_controller.end_turn(&mut self.state.players, &self.state.board)? {
+                    self.state.emit(event);
+                }
+                return Ok(());
+            }
+            GameCommand::ResolveDecision(decision) => {
+                // Only the seat being asked gets here, so the pending decision is theirs.
+                if let Some(pending) = &self.state.pending_decision {
+                    if !pending.request.is_valid(&decision, &self.state.players[seat].hand) {
+                        return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("That isn't a valid answer to the decision.")}));
+                    }
+                }
+
+                self.state.pending_decision = None;
+                self.state.decision_answer = Some(decision);
+                self.run_effects();
+                return Ok(());
+            }
+        }
+    }
+
+    // play_card
+    // The current player plays a card from their hand (shorthand for applying GameCommand::PlayCard).
+    // If the card has an effect that needs a decision, the game may stop with a decision pending.
+    pub fn play_card(&mut self, index: usize) -> Result<Vec<GameEvent>, GameErrors> {
+        let seat = self.state.turn_controller.get_current_player();
+        return self.apply(seat, GameCommand::PlayCard { hand_index: index });
+    }
+
+    // resolve_decision
+    // Answer the decision the game is waiting on, and carry on from where it stopped (shorthand for applying GameCommand::ResolveDecision).
+    // Returns an error (and keeps waiting) if the answer doesn't fit the request.
+    pub fn resolve_decision(&mut self, decision: Decision) -> Result<Vec<GameEvent>, GameErrors> {
+        let seat = match &self.state.pending_decision {
+            Some(pending) => pending.seat,
+            None => self.state.turn_controller.get_current_player()
+        };
+        return self.apply(seat, GameCommand::ResolveDecision(decision));
+    }
+
+    // run_effects
+    // Resolve card effects off the top of the stack until they're all done, or one is waiting on a deferred decision.
+    fn run_effects(&mut self) {
+        while self.state.pending_decision.is_none() {
+            let frame = match self.state.effects.pop() {
+                Some(f) => f,
+                None => return
+            };
+
+            let answer = self.state.decision_answer.take();
+            match card_effects::step(&mut self.state, frame, answer) {
+                EffectStep::Done => {}
+                EffectStep::Continue(frame) => {
+                    self.state.effects.push(frame);
+                }
+                EffectStep::Then(frame, first) => {
+                    self.state.effects.push(frame);
+                    self.state.effects.push(first);
+                }
+                EffectStep::Ask(frame, seat, request) => {
+                    self.state.effects.push(frame);
+
+                    // Ask the seat's agent. If it answers badly, fall back on the simplest valid answer,
+                    // as a broken bot shouldn't be able to stall the game.
+                    let view = DecisionView { seat: seat, hand: &self.state.players[seat].hand };
+                    // Answers are recorded like moves (by execute, after the move they were asked during), so replays don't need to ask the agent again.
+                    let answer = match self.agents[seat].decide(&view, &request) {
+                        Some(d) if request.is_valid(&d, &self.state.players[seat].hand) => d,
+                        Some(_) => request.default_decision(&self.state.players[seat].hand),
+                        None => {
+                            self.state.pending_decision = Some(PendingDecision { seat: seat, request: request });
+                            continue;
+                        }
+                    };
+                    self.answers.push(RecordedMove { seat: seat, command: GameCommand::ResolveDecision(answer.clone()) });
+                    self.state.decision_answer = Some(answer);
+                }
+            }
+        }
+    }
+
+    // advance_phase
+    // Moves the current player on to their next phase (shorthand for applying GameCommand::EndPhase).
+    // Leaving the buy phase ends the turn, after which any bots play before this returns.
+    pub fn advance_phase(&mut self) -> Result<Vec<GameEvent>, GameErrors> {
+        let seat = self.state.turn_controller.get_current_player();
+        return self.apply(seat, GameCommand::EndPhase);
+    }
+
+    // end_turn
+    // Ends the current player's turn, and passes the turn to the next seat (shorthand for applying GameCommand::EndTurn).
+    // Any bots play their turns before this returns.
+    pub fn end_turn(&mut self) -> Result<Vec<GameEvent>, GameErrors> {
+        let seat = self.state.turn_controller.get_current_player();
+        return self.apply(seat, GameCommand::EndTurn);
+    }
+
+    // bot_turn
+    // Play the current seat's turn with its strategy: the actions it picks, then every treasure, then the cards it picks to buy.
+    // If a human has to answer something partway through (an attack, say), it stops, and carries on from the same phase next time.
+    fn bot_turn(&mut self) {
+        let seat = self.state.turn_controller.get_current_player();
+
+        // Play actions until the strategy stops, or one can't be played.
+        while self.state.players[seat].phase == PlayerPhases::Action && self.state.players[seat].actions_remaining > 0 {
+            let view = StrategyView::new(seat, &self.state);
+            let hand_index = match self.strategies[seat].choose_action(&view) {
+                Some(i) => i,
+                None => break
+            };
+            if self.execute(seat, GameCommand::PlayCard { hand_index: hand_index }).is_err() {
+                break;
+            }
+            if self.state.pending_decision.is_some() || self.state.turn_controller.is_game_over() {
+                return;
+            }
+        }
+
+        // Advance phase until we can play our treasures.
+        while self.state.players[seat].phase != PlayerPhases::Buy {
+            if self.execute(seat, GameCommand::EndPhase).is_err() {
+                return;
+            }
+        }
+
+        // Play all treasures, then buy until the strategy stops, or it picks something that can't be bought.
+        let _ = self.execute(seat, GameCommand::PlayAllTreasures);
+        while self.state.players[seat].buys > 0 {
+            let view = StrategyView::new(seat, &self.state);
+            let pile = self.strategies[seat].choose_buy(&view).and_then(|card| self.state.board.find_pile(card));
+            let (index, set) = match pile {
+                Some(p) => p,
+                None => break
+            };
+            if self.execute(seat, GameCommand::Buy { set: set, index: index }).is_err() {
+                break;
+            }
+        }
+
+        // Cleanup, and pass the turn on.
+        let _ = self.execute(seat, GameCommand::EndTurn);
+    }
+
+    // play_bot_turns
+    // Plays every bot seat's turn until it's a human's turn again.
+    // Stops early if the game ends partway around the table, or a human has to make a decision.
+    // Returns every event since the last move (the bots' turns included), once they've been sent out.
+    pub fn play_bot_turns(&mut self) -> Vec<GameEvent> {
+        while !self.state.turn_controller.is_game_over() && self.state.pending_decision.is_none() && self.state.players[self.state.turn_controller.get_current_player()].kind == SeatKind::Bot {
+            self.bot_turn();
+        }
+        return self.publish();
+    }
+
+    // check_ending
+    // The TurnController checks for the end of the game after every turn.
+    // If the game has ended, it returns every seat ranked by score, with ties broken by fewest turns taken.
+    // If the game hasn't ended, it returns nothing.
+    pub fn check_ending(&self) -> Option<GameResult> {
+        return self.state.turn_controller.get_result().cloned();
     }
     
     pub fn get_pile_desc(&mut self, index: usize, card_set: CardSet) -> Result<String, GameErrors> {
-        return self.board.borrow().get_pile_desc(index, card_set)
+        return self.state.board.get_pile_desc(index, card_set)
     }
 }
 
@@ -215,3 +713,505 @@ pub struct PileData {
 
 
 
+
+/**
+ * Card tests
+ * Each kingdom card played in a seeded two-player game, with the hands and decks it needs set up by hand,
+ * and every decision it asks for answered by a ScriptedAgent.
+ * The helpers below are the only part that reaches into the game, so the tests themselves only deal in card names.
+ */
+#[cfg(test)]
+mod card_tests {
+    use super::*;
+    use crate::card_manager::card_registry::CardId;
+    use crate::player::player::Player;
+    use crate::card_manager::card_properties::CardTypes;
+    use crate::player::agent::ScriptedAgent;
+
+    const SEED: u64 = 7;
+
+    /**
+     * Helper: a game between two people, waiting on the first seat's first action.
+     */
+    fn game() -> GameManager {
+        return GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(SEED)).expect("The test game should set up.");
+    }
+
+    /**
+     * Helper: a new copy of the named card.
+     */
+    fn card(name: &str) -> CardId {
+        return CardId::named(name).expect("The test cards are all in the base set.");
+    }
+
+    fn names<'a>(cards: impl Iterator<Item = &'a CardId>) -> Vec<String> {
+        return cards.map(|c| c.get_properties().name.clone()).collect();
+    }
+
+    // Setting up a seat's cards, the first in a deck or discard pile being the top.
+    fn set_hand(gm: &mut GameManager, seat: usize, cards: &[&str]) {
+        gm.state.players[seat].hand = cards.iter().map(|c| card(c)).collect();
+    }
+
+    fn set_deck(gm: &mut GameManager, seat: usize, cards: &[&str]) {
+        gm.state.players[seat].deck = cards.iter().map(|c| card(c)).collect();
+    }
+
+    fn set_discard(gm: &mut GameManager, seat: usize, cards: &[&str]) {
+        gm.state.players[seat].discard = cards.iter().map(|c| card(c)).collect();
+    }
+
+    fn empty_first_kingdom_pile(gm: &mut GameManager) {
+        gm.state.board.kingdom_set[0].cards_left = 0;
+    }
+
+    // Answer the seat's decisions from the script, in order.
+    fn script(gm: &mut GameManager, seat: usize, answers: Vec<Decision>) {
+        gm.set_agent(seat, Box::new(ScriptedAgent::new(answers)));
+    }
+
+    // The answer picking the named card out of the supply piles a card offers.
+    fn supply_pick(gm: &GameManager, max_cost: i32, card_type: Option<CardTypes>, name: &str) -> Decision {
+        let choices = gm.state.board.get_supply_choices(max_cost, card_type);
+        return Decision::SupplyPile(choices.iter().position(|c| c.name == name));
+    }
+
+    // Play the first card in the first seat's hand, and check nothing is left waiting on a decision.
+    fn play_first(gm: &mut GameManager) {
+        gm.play_card(0).expect("The card should be playable.");
+        assert!(gm.get_pending_decision().is_none());
+    }
+
+    fn play_treasures(gm: &mut GameManager) {
+        gm.state.players[0].play_treasures();
+    }
+
+    // Reading a seat's cards back, by name.
+    fn hand(gm: &GameManager, seat: usize) -> Vec<String> {
+        return names(gm.state.players[seat].hand.iter());
+    }
+
+    fn deck(gm: &GameManager, seat: usize) -> Vec<String> {
+        return names(gm.state.players[seat].deck.iter());
+    }
+
+    fn discard(gm: &GameManager, seat: usize) -> Vec<String> {
+        return names(gm.state.players[seat].discard.iter());
+    }
+
+    fn in_play(gm: &GameManager, seat: usize) -> Vec<String> {
+        return names(gm.state.players[seat].in_play.iter());
+    }
+
+    fn player(gm: &GameManager, seat: usize) -> &Player {
+        return &gm.state.players[seat];
+    }
+
+    fn trashed(gm: &GameManager, name: &str) -> usize {
+        return gm.state.board.count_in_trash(card(name));
+    }
+
+    fn cards_left(gm: &GameManager, name: &str) -> u8 {
+        let board = &gm.state.board;
+        let piles = board.victory_cards.iter().chain(board.treasure_cards.iter()).chain(board.kingdom_set.iter());
+        return piles.filter(|p| p.get_card_name() == name).map(|p| p.cards_left).sum();
+    }
+
+    #[test]
+    fn cellar_discards_and_draws_as_many() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Cellar", "Estate", "Estate", "Copper"]);
+        set_deck(&mut gm, 0, &["Gold", "Silver"]);
+        set_discard(&mut gm, 0, &[]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0, 1])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper", "Gold", "Silver"]);
+        assert_eq!(discard(&gm, 0), ["Estate", "Estate"]);
+        assert_eq!(player(&gm, 0).actions_remaining, 1);
+    }
+
+    #[test]
+    fn chapel_trashes_up_to_four() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Chapel", "Estate", "Estate", "Copper", "Copper"]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0, 1, 2])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"]);
+        assert_eq!(trashed(&gm, "Estate"), 2);
+        assert_eq!(trashed(&gm, "Copper"), 1);
+    }
+
+    #[test]
+    fn moat_blocks_an_attack() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Militia"]);
+        set_hand(&mut gm, 1, &["Moat", "Copper", "Copper", "Estate", "Estate"]);
+        set_discard(&mut gm, 1, &[]);
+        script(&mut gm, 1, vec![Decision::YesNo(true)]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 1).len(), 5);
+        assert!(discard(&gm, 1).is_empty());
+        assert_eq!(player(&gm, 0).buy_power, 2);
+    }
+
+    #[test]
+    fn harbinger_puts_a_discarded_card_on_the_deck() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Harbinger"]);
+        set_deck(&mut gm, 0, &["Copper", "Copper"]);
+        set_discard(&mut gm, 0, &["Gold", "Estate"]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"]);
+        assert_eq!(deck(&gm, 0), ["Gold", "Copper"]);
+        assert_eq!(discard(&gm, 0), ["Estate"]);
+    }
+
+    #[test]
+    fn merchant_adds_a_coin_to_the_first_silver() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Merchant", "Silver", "Silver"]);
+        set_deck(&mut gm, 0, &["Copper"]);
+        play_first(&mut gm);
+        gm.advance_phase().unwrap();
+        play_treasures(&mut gm);
+
+        assert_eq!(player(&gm, 0).phase, PlayerPhases::Buy);
+        assert_eq!(player(&gm, 0).buy_power, 2 + 2 + 1 + 1);
+    }
+
+    #[test]
+    fn vassal_plays_the_discarded_action() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Vassal"]);
+        set_deck(&mut gm, 0, &["Smithy", "Copper", "Copper", "Copper", "Estate"]);
+        set_discard(&mut gm, 0, &[]);
+        script(&mut gm, 0, vec![Decision::YesNo(true)]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper", "Copper", "Copper"]);
+        assert_eq!(in_play(&gm, 0), ["Vassal", "Smithy"]);
+        assert!(discard(&gm, 0).is_empty());
+        assert_eq!(player(&gm, 0).buy_power, 2);
+    }
+
+    #[test]
+    fn workshop_gains_a_card_costing_up_to_four() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Workshop"]);
+        set_discard(&mut gm, 0, &[]);
+        let pick = supply_pick(&gm, 4, None, "Silver");
+        script(&mut gm, 0, vec![pick]);
+        play_first(&mut gm);
+
+        assert_eq!(discard(&gm, 0), ["Silver"]);
+    }
+
+    #[test]
+    fn bureaucrat_gains_a_silver_and_topdecks_a_victory() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Bureaucrat"]);
+        set_hand(&mut gm, 1, &["Copper", "Estate", "Copper", "Copper", "Copper"]);
+        play_first(&mut gm);
+
+        assert_eq!(deck(&gm, 0)[0], "Silver");
+        assert_eq!(deck(&gm, 1)[0], "Estate");
+        assert_eq!(hand(&gm, 1), ["Copper", "Copper", "Copper", "Copper"]);
+    }
+
+    #[test]
+    fn gardens_score_a_point_per_ten_cards() {
+        let mut gm = game();
+        let mut cards = vec!["Gardens", "Gardens", "Estate"];
+        cards.extend(["Copper"; 26]);
+        set_hand(&mut gm, 0, &[]);
+        set_deck(&mut gm, 0, &cards);
+        set_discard(&mut gm, 0, &[]);
+
+        // 29 cards: each Gardens is worth 2, plus the Estate.
+        assert_eq!(player(&gm, 0).get_victory_points(), 2 + 2 + 1);
+    }
+
+    #[test]
+    fn militia_makes_others_discard_down_to_three() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Militia"]);
+        set_hand(&mut gm, 1, &["Copper", "Copper", "Estate", "Estate", "Silver"]);
+        set_discard(&mut gm, 1, &[]);
+        script(&mut gm, 1, vec![Decision::Cards(vec![2, 3])]);
+        play_first(&mut gm);
+
+        assert_eq!(player(&gm, 0).buy_power, 2);
+        assert_eq!(hand(&gm, 1), ["Copper", "Copper", "Silver"]);
+        assert_eq!(discard(&gm, 1), ["Estate", "Estate"]);
+    }
+
+    #[test]
+    fn moneylender_trashes_a_copper_for_three() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Moneylender", "Estate", "Copper"]);
+        script(&mut gm, 0, vec![Decision::YesNo(true)]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Estate"]);
+        assert_eq!(player(&gm, 0).buy_power, 3);
+        assert_eq!(trashed(&gm, "Copper"), 1);
+    }
+
+    #[test]
+    fn poacher_discards_a_card_per_empty_pile() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Poacher", "Estate", "Copper"]);
+        set_deck(&mut gm, 0, &["Copper"]);
+        set_discard(&mut gm, 0, &[]);
+        empty_first_kingdom_pile(&mut gm);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper", "Copper"]);
+        assert_eq!(discard(&gm, 0), ["Estate"]);
+        assert_eq!(player(&gm, 0).buy_power, 1);
+    }
+
+    #[test]
+    fn remodel_trashes_and_gains_up_to_two_more() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Remodel", "Estate", "Copper"]);
+        set_discard(&mut gm, 0, &[]);
+        let pick = supply_pick(&gm, 4, None, "Silver");
+        script(&mut gm, 0, vec![Decision::Cards(vec![0]), pick]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"]);
+        assert_eq!(discard(&gm, 0), ["Silver"]);
+        assert_eq!(trashed(&gm, "Estate"), 1);
+    }
+
+    #[test]
+    fn throne_room_plays_an_action_twice() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Throne Room", "Smithy"]);
+        set_deck(&mut gm, 0, &["Copper"; 7]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"; 6]);
+        assert_eq!(in_play(&gm, 0), ["Throne Room", "Smithy"]);
+        assert_eq!(deck(&gm, 0).len(), 1);
+    }
+
+    #[test]
+    fn bandit_gains_a_gold_and_trashes_a_revealed_treasure() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Bandit"]);
+        set_discard(&mut gm, 0, &[]);
+        set_deck(&mut gm, 1, &["Gold", "Silver", "Estate"]);
+        set_discard(&mut gm, 1, &[]);
+        script(&mut gm, 1, vec![Decision::Cards(vec![1])]);
+        play_first(&mut gm);
+
+        assert_eq!(discard(&gm, 0), ["Gold"]);
+        assert_eq!(trashed(&gm, "Silver"), 1);
+        assert_eq!(discard(&gm, 1), ["Gold"]);
+        assert_eq!(deck(&gm, 1), ["Estate"]);
+    }
+
+    #[test]
+    fn council_room_draws_four_and_others_draw_one() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Council Room"]);
+        set_deck(&mut gm, 0, &["Copper"; 4]);
+        set_deck(&mut gm, 1, &["Gold"]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"; 4]);
+        assert_eq!(player(&gm, 0).buys, 2);
+        assert_eq!(hand(&gm, 1).len(), 6);
+        assert_eq!(hand(&gm, 1)[5], "Gold");
+    }
+
+    #[test]
+    fn library_draws_to_seven_setting_aside_actions() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Library", "Copper", "Copper"]);
+        set_deck(&mut gm, 0, &["Smithy", "Copper", "Copper", "Village", "Copper", "Copper", "Estate"]);
+        set_discard(&mut gm, 0, &[]);
+        script(&mut gm, 0, vec![Decision::YesNo(true), Decision::YesNo(false)]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper", "Copper", "Copper", "Copper", "Village", "Copper", "Copper"]);
+        assert_eq!(discard(&gm, 0), ["Smithy"]);
+        assert_eq!(deck(&gm, 0), ["Estate"]);
+    }
+
+    #[test]
+    fn mine_trashes_a_treasure_and_gains_a_better_one_to_hand() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Mine", "Silver", "Estate"]);
+        let pick = supply_pick(&gm, 6, Some(CardTypes::Treasure), "Gold");
+        script(&mut gm, 0, vec![Decision::Cards(vec![0]), pick]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Estate", "Gold"]);
+        assert_eq!(trashed(&gm, "Silver"), 1);
+    }
+
+    #[test]
+    fn sentry_trashes_discards_and_puts_back() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Sentry"]);
+        set_deck(&mut gm, 0, &["Copper", "Curse", "Estate", "Gold"]);
+        set_discard(&mut gm, 0, &[]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![0]), Decision::Cards(vec![0])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper"]);
+        assert_eq!(trashed(&gm, "Curse"), 1);
+        assert_eq!(discard(&gm, 0), ["Estate"]);
+        assert_eq!(deck(&gm, 0), ["Gold"]);
+    }
+
+    #[test]
+    fn sentry_puts_kept_cards_back_in_the_chosen_order() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Sentry"]);
+        set_deck(&mut gm, 0, &["Copper", "Silver", "Gold", "Estate"]);
+        script(&mut gm, 0, vec![Decision::Cards(vec![]), Decision::Cards(vec![]), Decision::Order(vec![1, 0])]);
+        play_first(&mut gm);
+
+        assert_eq!(deck(&gm, 0), ["Gold", "Silver", "Estate"]);
+    }
+
+    #[test]
+    fn witch_gives_others_a_curse() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Witch"]);
+        set_deck(&mut gm, 0, &["Copper", "Copper"]);
+        set_discard(&mut gm, 1, &[]);
+        let curses = cards_left(&gm, "Curse");
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Copper", "Copper"]);
+        assert_eq!(discard(&gm, 1), ["Curse"]);
+        assert_eq!(cards_left(&gm, "Curse"), curses - 1);
+    }
+
+    #[test]
+    fn artisan_gains_to_hand_and_topdecks_a_card() {
+        let mut gm = game();
+        set_hand(&mut gm, 0, &["Artisan", "Estate"]);
+        let pick = supply_pick(&gm, 5, None, "Silver");
+        script(&mut gm, 0, vec![pick, Decision::Cards(vec![0])]);
+        play_first(&mut gm);
+
+        assert_eq!(hand(&gm, 0), ["Silver"]);
+        assert_eq!(deck(&gm, 0)[0], "Estate");
+    }
+}
+
+/**
+ * Seed tests
+ * Games set up from the same seed, checked to deal everyone the same cards.
+ */
+#[cfg(test)]
+mod seed_tests {
+    use super::*;
+
+    // Every seat's hand and deck, by name, in order.
+    fn opening_cards(seed: u64) -> Vec<(Vec<String>, Vec<String>)> {
+        let seats = vec![Seat::human("Alice"), Seat::human("Bob"), Seat::human("Carol")];
+        let gm = GameManager::new(seats, Some(seed)).expect("The test game should set up.");
+        return gm.state.players.iter()
+            .map(|p| (p.hand.iter().map(|c| c.get_name().clone()).collect(), p.deck.iter().map(|c| c.get_name().clone()).collect()))
+            .collect();
+    }
+
+    #[test]
+    fn the_same_seed_deals_the_same_opening_hands() {
+        assert_eq!(opening_cards(42), opening_cards(42));
+        assert_eq!(GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(42)).unwrap().get_seed(), 42);
+    }
+
+    #[test]
+    fn different_seeds_shuffle_differently() {
+        assert_ne!(opening_cards(1), opening_cards(2));
+    }
+}
+
+/**
+ * Legal move tests
+ * A seeded game played out one legal move at a time, checking at every step that apply takes exactly the moves legal_moves lists.
+ */
+#[cfg(test)]
+mod legal_move_tests {
+    use super::*;
+
+    // Every move worth trying: each card in the seat's hand (and one past the end), each supply pile (and one past the end of each set),
+    // every answer legal_moves offers and a few it doesn't, and moving on.
+    fn candidate_moves(gm: &GameManager, seat: usize) -> Vec<GameCommand> {
+        let mut moves = vec![GameCommand::PlayAllTreasures, GameCommand::EndPhase, GameCommand::EndTurn];
+        for hand_index in 0..=gm.state.players[seat].hand.len() {
+            moves.push(GameCommand::PlayCard { hand_index: hand_index });
+        }
+
+        let board = &gm.state.board;
+        let sets = [(CardSet::Treasures, board.treasure_cards.len()), (CardSet::Victories, board.victory_cards.len()), (CardSet::Kingdoms, board.kingdom_set.len())];
+        for (set, len) in sets {
+            for index in 0..=len {
+                moves.push(GameCommand::Buy { set: set, index: index });
+            }
+        }
+
+        for decision in [Decision::Cards(Vec::new()), Decision::Cards(vec![0]), Decision::Cards(vec![99]), Decision::SupplyPile(None),
+                         Decision::SupplyPile(Some(0)), Decision::SupplyPile(Some(99)), Decision::YesNo(true), Decision::Order(vec![0, 0])] {
+            moves.push(GameCommand::ResolveDecision(decision));
+        }
+        for command in gm.legal_moves(seat) {
+            if !moves.contains(&command) {
+                moves.push(command);
+            }
+        }
+        return moves;
+    }
+
+    // Check every candidate move for every seat against a copy of the game.
+    fn check_every_move(gm: &GameManager) {
+        for seat in 0..gm.get_player_count() {
+            let legal = gm.legal_moves(seat);
+            for command in candidate_moves(gm, seat) {
+                let mut copy = GameManager::from_state(gm.get_state().clone());
+                let accepted = copy.apply(seat, command.clone()).is_ok();
+                assert_eq!(accepted, legal.contains(&command), "seat {} trying {:?}", seat, command);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_takes_exactly_the_legal_moves() {
+        let seats = vec![Seat::human("Alice"), Seat::human("Bob")];
+        let mut gm = GameManager::with_kingdom(seats, Some(21), KingdomChoice::Preset(String::from("First Game"))).unwrap();
+
+        // Walk through the game, picking moves in a fixed but varied order (and only ending the turn when nothing else is left).
+        let mut pick = 0;
+        for _ in 0..300 {
+            if gm.check_ending().is_some() {
+                break;
+            }
+            check_every_move(&gm);
+
+            let seat = match gm.get_pending_decision() {
+                Some(pending) => pending.seat,
+                None => gm.get_current_player_index()
+            };
+            let moves = gm.legal_moves(seat);
+            let choices: Vec<&GameCommand> = moves.iter().filter(|m| **m != GameCommand::EndTurn).collect();
+            let command = if choices.is_empty() { GameCommand::EndTurn } else { choices[pick % choices.len()].clone() };
+            gm.apply(seat, command).expect("A legal move should be accepted.");
+            pick = (pick * 7 + 3) % 1000;
+        }
+    }
+}
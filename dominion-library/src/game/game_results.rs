@@ -0,0 +1,122 @@
+/*
+SPUStudnet
+12/15/2024
+game_results.rs
+Defines the final standings of a finished game, ranked by the official scoring and tiebreak rules.
+*/
+
+use serde::{Deserialize, Serialize};
+
+/**
+ * PlayerStanding
+ * Where one seat finished: their score, how many turns they took, and their final rank.
+ * Rank 1 is the winner. Players who tie completely share the same rank.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerStanding {
+    pub seat: usize,
+    pub name: String,
+    pub victory_points: i32,
+    pub turns_taken: u32,
+    pub rank: usize
+}
+
+/**
+ * GameResult
+ * The standings of every seat, ordered from first place to last.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameResult {
+    pub standings: Vec<PlayerStanding>
+}
+
+/**
+ * GameResult implementation
+ */
+impl GameResult {
+    /**
+     * rank
+     * Rank a list of (seat, name, victory points, turns taken) entries.
+     * The most victory points wins. If that's tied, the tied player who took fewer turns wins.
+     * If that's still tied, the players share the rank (and share the victory, if it's first place).
+     */
+    pub(crate) fn rank(mut entries: Vec<(usize, String, i32, u32)>) -> GameResult {
+        // Sort by points (highest first), then by turns taken (fewest first), then by seat so the order is stable.
+        entries.sort_by(|a, b| b.2.cmp(&a.2).then(a.3.cmp(&b.3)).then(a.0.cmp(&b.0)));
+
+        let mut standings: Vec<PlayerStanding> = Vec::new();
+        for (position, (seat, name, vp, turns)) in entries.into_iter().enumerate() {
+            // Share the rank with the player above if they tied on both points and turns.
+            let rank = match standings.last() {
+                Some(prev) if prev.victory_points == vp && prev.turns_taken == turns => prev.rank,
+                _ => position + 1
+            };
+
+            standings.push(PlayerStanding {
+                seat: seat,
+                name: name,
+                victory_points: vp,
+                turns_taken: turns,
+                rank: rank
+            });
+        }
+
+        return GameResult { standings: standings };
+    }
+
+    /**
+     * get_winners
+     * Every player in first place. More than one means a shared victory.
+     */
+    pub fn get_winners(&self) -> Vec<&PlayerStanding> {
+        return self.standings.iter().filter(|s| s.rank == 1).collect();
+    }
+
+    /**
+     * is_shared_victory
+     * True if more than one player tied for first.
+     */
+    pub fn is_shared_victory(&self) -> bool {
+        return self.get_winners().len() > 1;
+    }
+}
+
+/**
+ * Ranking tests
+ * Final scores ranked by points, then by turns taken, with complete ties sharing a rank.
+ */
+#[cfg(test)]
+mod ranking_tests {
+    use super::*;
+
+    // Rank (seat, victory points, turns taken) entries, and return (seat, rank) from first place to last.
+    fn ranks(entries: &[(usize, i32, u32)]) -> Vec<(usize, usize)> {
+        let entries = entries.iter().map(|(seat, vp, turns)| (*seat, format!("Seat {}", seat), *vp, *turns)).collect();
+        return GameResult::rank(entries).standings.iter().map(|s| (s.seat, s.rank)).collect();
+    }
+
+    #[test]
+    fn most_points_wins() {
+        assert_eq!(ranks(&[(0, 20, 15), (1, 31, 15), (2, 25, 14)]), [(1, 1), (2, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn tied_points_go_to_fewer_turns() {
+        // The second seat tied the first seat's points, but took a turn less.
+        let result = GameResult::rank(vec![(0, String::from("Alice"), 30, 16), (1, String::from("Bob"), 30, 15)]);
+
+        assert_eq!(result.standings.iter().map(|s| (s.seat, s.rank)).collect::<Vec<_>>(), [(1, 1), (0, 2)]);
+        assert!(!result.is_shared_victory());
+        assert_eq!(result.get_winners()[0].name, "Bob");
+    }
+
+    #[test]
+    fn complete_ties_share_a_rank() {
+        assert_eq!(ranks(&[(0, 30, 15), (1, 30, 15), (2, 12, 15)]), [(0, 1), (1, 1), (2, 3)]);
+        assert_eq!(ranks(&[(0, 40, 15), (1, 30, 15), (2, 30, 15)]), [(0, 1), (1, 2), (2, 2)]);
+
+        let result = GameResult::rank(vec![(0, String::from("Alice"), 30, 15), (1, String::from("Bob"), 30, 15)]);
+        assert!(result.is_shared_victory());
+        assert_eq!(result.get_winners().len(), 2);
+    }
+}
@@ -0,0 +1,380 @@
+/*
+SPUStudnet
+12/15/2024
+game_state.rs
+Defines the game state: everything about a game in progress, owned from this one struct.
+The players, the board, the log and the turn order are all plain data held here, with no shared references between them.
+Anything that needs more than one of them at once (like a player buying from the supply) is done here, by seat.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use static_assertions::assert_impl_all;
+
+use crate::card_manager::card_registry::CardId;
+use crate::game::board::{Board, CardSet, PlayerInterface};
+use crate::game::card_effects::EffectFrame;
+use crate::game::game_errors::{CardNotFoundError, GameErrors, InvalidActionError};
+use crate::game::game_event::GameEvent;
+use crate::game::game_log::GameLog;
+use crate::game::kingdom::KingdomChoice;
+use crate::game::replay::RecordedMove;
+use crate::game::pile_builder::{build_default_player_deck, build_kingdom, build_supply};
+use crate::game::turn_controller::TurnController;
+use crate::player::agent::{Decision, PendingDecision};
+use crate::player::phases::PlayerPhases;
+use crate::player::player::{GainDestination, Player};
+use crate::player::seat::Seat;
+
+/**
+ * GameState struct
+ * The state of one game.
+ * Holds every player (in turn order), the board, the log, whose turn it is, and any card effects that are partway resolved.
+ * Cloning it copies the game exactly, shuffles to come and all. Use fork to look ahead without knowing how the shuffles will go.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub(crate) players: Vec<Player>,
+    pub(crate) board: Board,
+    pub(crate) turn_controller: TurnController,
+    pub(crate) log: GameLog,
+
+    // Events that have happened since the game last sent them out to its subscribers, oldest first.
+    // They've always been sent by the time a move is finished, so they aren't saved.
+    #[serde(skip)]
+    pub(crate) events: Vec<GameEvent>,
+
+    // Every move made so far, in order (see replay.rs).
+    // Forks don't have one, as they shuffle differently from the seed and couldn't be replayed from it.
+    pub(crate) history: Option<Vec<RecordedMove>>,
+
+    // The seed the game was created with.
+    // The kingdom is picked from it, and each player shuffles with an rng of their own seeded from it on a different stream.
+    pub(crate) seed: u64,
+
+    // Card effects partway resolved (the top of the stack is resolved first),
+    // the decision the game is waiting on, and the answer to hand to the next step of an effect.
+    pub(crate) effects: Vec<EffectFrame>,
+    pub(crate) pending_decision: Option<PendingDecision>,
+    pub(crate) decision_answer: Option<Decision>
+}
+
+// The state is plain data owned from here, so it can be copied, moved to another thread, and read from many at once.
+assert_impl_all!(GameState: Clone, Send, Sync);
+
+/**
+ * GameState implementation
+ */
+impl GameState {
+    /**
+     * new
+     * Set up a game for the given seats: the supply, the kingdom, and every player's deck, shuffled and drawn.
+     * The kingdom is picked before any shuffling, so a random kingdom is the first thing drawn from the seed.
+     * No turn is started yet; that's up to whoever is running the game.
+     */
+    pub(crate) fn new(seats: &[Seat], seed: u64, kingdom: &KingdomChoice) -> Result<GameState, GameErrors> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        // Create the board, with the basic supply sized for the number of seats at the table.
+        let (treasure_piles, victory_piles) = build_supply(seats.len());
+        let kingdom_cards = kingdom.choose_cards(&mut rng)?;
+        let board = Board {
+            victory_cards: victory_piles,
+            treasure_cards: treasure_piles,
+            kingdom_set: build_kingdom(&kingdom_cards, seats.len())?,
+            trash: Vec::new()
+        };
+
+        // Record which kingdom is being played.
+        let mut events = vec![GameEvent::KingdomChosen {
+            source: kingdom.describe(),
+            cards: kingdom_cards.iter().filter_map(|name| CardId::named(name)).collect()
+        }];
+
+        // Create a player for every seat, with their own rng stream (stream 0 is the game's).
+        // Then shuffle their deck and draw their hand.
+        let mut players = Vec::new();
+        for (index, seat) in seats.iter().enumerate() {
+            let mut player_rng = ChaCha8Rng::seed_from_u64(seed);
+            player_rng.set_stream(index as u64 + 1);
+
+            let mut player = Player::new(index, build_default_player_deck(), player_rng);
+            player.name = seat.name.clone();
+            player.kind = seat.kind;
+            player.strategy = seat.strategy;
+            player.shuffle_deck();
+            player.cleanup_and_draw();
+            events.append(&mut player.events);
+            players.push(player);
+        }
+
+        return Ok(GameState {
+            log: GameLog::new(seats.iter().map(|s| s.name.clone()).collect()),
+            players: players,
+            board: board,
+            turn_controller: TurnController::new(),
+            events: events,
+            history: Some(Vec::new()),
+            seed: seed,
+            effects: Vec::new(),
+            pending_decision: None,
+            decision_answer: None
+        });
+    }
+
+    /**
+     * fork
+     * Make an independent copy of the game to try things out on, like "what if I buy a Gold here?".
+     * Nothing done to the fork changes this game, or the other way around.
+     * Every player in the fork shuffles from a new rng stream seeded from fork_seed, so forks with different seeds
+     * play out different futures, while the same seed always plays out the same one.
+     * The fork's log (and its events waiting to be sent out) start empty, as looking ahead doesn't need the history.
+     * It keeps this game's seed, though what happens after the fork can't be replayed from that seed, so it has no history to replay.
+     */
+    pub fn fork(&self, fork_seed: u64) -> GameState {
+        let mut players = self.players.clone();
+        for (index, player) in players.iter_mut().enumerate() {
+            player.rng = ChaCha8Rng::seed_from_u64(fork_seed);
+            player.rng.set_stream(index as u64 + 1);
+        }
+
+        return GameState {
+            players: players,
+            board: self.board.clone(),
+            turn_controller: self.turn_controller.clone(),
+            log: self.log.fork(),
+            events: Vec::new(),
+            history: None,
+            seed: self.seed,
+            effects: self.effects.clone(),
+            pending_decision: self.pending_decision.clone(),
+            decision_answer: self.decision_answer.clone()
+        };
+    }
+
+    /**
+     * emit
+     * Record that something happened, after anything the players have recorded (like draws) that came before it.
+     */
+    pub(crate) fn emit(&mut self, event: GameEvent) {
+        self.collect_player_events();
+        self.events.push(event);
+    }
+
+    /**
+     * collect_player_events
+     * Move every player's events into the game's, so they go out in the order they happened.
+     */
+    pub(crate) fn collect_player_events(&mut self) {
+        for player in self.players.iter_mut() {
+            self.events.append(&mut player.events);
+        }
+    }
+
+    /**
+     * take_events
+     * Every event since the last time this was called, oldest first.
+     */
+    pub(crate) fn take_events(&mut self) -> Vec<GameEvent> {
+        self.collect_player_events();
+        return std::mem::take(&mut self.events);
+    }
+
+    /**
+     * draw_cards
+     * The player in the given seat draws cards (because a card said to).
+     */
+    pub(crate) fn draw_cards(&mut self, seat: usize, count: usize) {
+        self.players[seat].draw_cards(count);
+        self.collect_player_events();
+    }
+
+    /**
+     * buy_card
+     * The player in the given seat buys a card from the board, given an index and a cardset.
+     * Deducts from their buys and buy power as needed.
+     * Returns an error if there is one.
+     */
+    pub(crate) fn buy_card(&mut self, seat: usize, pile_index: usize, c: CardSet) -> Option<GameErrors> {
+        let player = &mut self.players[seat];
+        if player.phase != PlayerPhases::Buy {
+            return Some(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("You can only buy cards during your buy phase.")}))
+        }
+
+        let price = match self.board.get_card_price(pile_index, c) {
+            Ok(x) => x,
+            Err(error) => return Some(error)
+        };
+        if (player.buy_power as i32) < price || player.buys == 0 {
+            return Some(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("You are out of buys, or out of money. Wait until next turn!")}))
+        }
+
+        // Only pay for the card once it's been taken from the pile.
+        match self.board.buy_card(pile_index, c) {
+            Ok(x) => {
+                player.buys -= 1;
+                player.buy_power -= price as u8;
+                player.discard.push_front(x);
+                self.emit(GameEvent::CardBought { seat: seat, card: x });
+                return None;
+            }
+            Err(error) => {
+                return Some(error);
+            }
+        }
+    }
+
+    /**
+     * gain_card
+     * The player in the given seat gains a card from the board, given an index and a cardset, without paying for it.
+     * Returns the card gained, or an error if the pile can't give one.
+     */
+    pub(crate) fn gain_card(&mut self, seat: usize, pile_index: usize, c: CardSet, destination: GainDestination) -> Result<CardId, GameErrors> {
+        let card = self.board.buy_card(pile_index, c)?;
+        self.emit(GameEvent::CardGained { seat: seat, card: card });
+
+        let player = &mut self.players[seat];
+        match destination {
+            GainDestination::Discard => player.discard.push_front(card),
+            GainDestination::Hand => player.hand.push(card),
+            GainDestination::DeckTop => player.deck.push_front(card)
+        }
+
+        return Ok(card);
+    }
+
+    /**
+     * discard_card
+     * Put a card into a player's discard outside of cleanup (because a card said to), recording it.
+     */
+    pub(crate) fn discard_card(&mut self, seat: usize, card: CardId) {
+        self.emit(GameEvent::CardDiscarded { seat: seat, card: card });
+        self.players[seat].discard.push_front(card);
+    }
+
+    /**
+     * reveal_card
+     * Record a player revealing a card. Where the card goes is up to the caller.
+     */
+    pub(crate) fn reveal_card(&mut self, seat: usize, card: CardId) {
+        self.emit(GameEvent::CardRevealed { seat: seat, card: card });
+    }
+
+    /**
+     * trash_card
+     * Put a card in the trash, recording who trashed it.
+     */
+    pub(crate) fn trash_card(&mut self, seat: usize, card: CardId) {
+        self.emit(GameEvent::CardTrashed { seat: seat, card: card });
+        self.board.trash.push(card);
+    }
+
+    /**
+     * trash_from_hand
+     * Trash the card at the given index of a player's hand.
+     * Returns the card trashed, as most trashing cards care about what it was.
+     */
+    pub fn trash_from_hand(&mut self, seat: usize, index: usize) -> Result<CardId, GameErrors> {
+        if index >= self.players[seat].hand.len() {
+            return Err(GameErrors::CardNotFoundError(CardNotFoundError {index: index}));
+        }
+        let card = self.players[seat].hand.remove(index);
+        self.trash_card(seat, card);
+        return Ok(card);
+    }
+
+    /**
+     * trash_from_play
+     * Trash the card at the given index of a player's playing area.
+     * Used by cards that trash themselves.
+     */
+    pub fn trash_from_play(&mut self, seat: usize, index: usize) -> Result<CardId, GameErrors> {
+        if index >= self.players[seat].in_play.len() {
+            return Err(GameErrors::CardNotFoundError(CardNotFoundError {index: index}));
+        }
+        let card = self.players[seat].in_play.remove(index);
+        self.trash_card(seat, card);
+        return Ok(card);
+    }
+
+    /**
+     * trash_from_deck_top
+     * Trash the top card of a player's deck, shuffling their discard in first if the deck is empty.
+     * Returns nothing if the player has no cards left to trash.
+     */
+    pub fn trash_from_deck_top(&mut self, seat: usize) -> Option<CardId> {
+        let card = self.players[seat].take_from_deck_top()?;
+        self.trash_card(seat, card);
+        return Some(card);
+    }
+}
+
+/**
+ * Fork tests
+ * Forks of a game played on, checked to leave the game they came from as it was.
+ */
+#[cfg(test)]
+mod fork_tests {
+    use crate::card_manager::card::Card;
+    use crate::card_manager::card_registry::CardId;
+    use crate::game::game_command::GameCommand;
+    use crate::game::game_manager::GameManager;
+    use crate::player::seat::Seat;
+
+    // Everything a fork could change: each seat's cards and money, every pile's count, and whose turn it is.
+    fn snapshot(gm: &GameManager) -> (Vec<Vec<String>>, Vec<u8>, Vec<u8>, usize, u32) {
+        let state = gm.get_state();
+        let names = |cards: &mut dyn Iterator<Item = &CardId>| cards.map(|c| c.get_name().clone()).collect();
+        let cards = state.players.iter()
+            .flat_map(|p| [names(&mut p.hand.iter()), names(&mut p.deck.iter()), names(&mut p.discard.iter())])
+            .collect();
+        let board = &state.board;
+        let piles = board.treasure_cards.iter().chain(board.victory_cards.iter()).chain(board.kingdom_set.iter()).map(|p| p.cards_left).collect();
+        let money = state.players.iter().map(|p| p.buy_power).collect();
+        return (cards, piles, money, state.turn_controller.get_current_player(), state.turn_controller.get_turn_number());
+    }
+
+    // Play out the current seat's turn: all their treasures, a Silver, and on to the next seat.
+    fn play_a_turn(gm: &mut GameManager) {
+        let seat = gm.get_current_player_index();
+        gm.apply(seat, GameCommand::EndPhase).unwrap();
+        gm.apply(seat, GameCommand::PlayAllTreasures).unwrap();
+        let (index, set) = gm.get_state().board.find_pile(CardId::named("Silver").unwrap()).unwrap();
+        gm.apply(seat, GameCommand::Buy { set: set, index: index }).unwrap();
+        gm.apply(seat, GameCommand::EndTurn).unwrap();
+    }
+
+    #[test]
+    fn playing_on_a_fork_leaves_the_game_alone() {
+        let gm = GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(3)).unwrap();
+        let before = snapshot(&gm);
+
+        let mut fork = gm.fork(100);
+        for _ in 0..4 {
+            play_a_turn(&mut fork);
+        }
+
+        assert_ne!(snapshot(&fork), before);
+        assert_eq!(snapshot(&gm), before);
+        assert!(fork.get_replay().is_none());
+        assert!(gm.get_replay().is_some());
+    }
+
+    #[test]
+    fn forks_with_the_same_seed_play_out_the_same() {
+        let gm = GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(3)).unwrap();
+        let mut first = gm.fork(100);
+        let mut second = gm.fork(100);
+        let mut other = gm.fork(101);
+        for _ in 0..4 {
+            play_a_turn(&mut first);
+            play_a_turn(&mut second);
+            play_a_turn(&mut other);
+        }
+
+        assert_eq!(snapshot(&first), snapshot(&second));
+        assert_ne!(snapshot(&first), snapshot(&other));
+    }
+}
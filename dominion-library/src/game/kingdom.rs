@@ -0,0 +1,196 @@
+/*
+SPUStudnet
+12/15/2024
+kingdom.rs
+Picks the ten kingdom cards a game is played with: at random from the seed, from one of the official recommended sets,
+or from a list of card names.
+*/
+
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::card_manager::card::Card;
+use crate::card_manager::card_registry::card_registry;
+use crate::game::game_errors::{GameErrors, InvalidSetupError};
+use crate::game::kingdom_constraints::KingdomConstraints;
+use crate::game::pile_builder::is_kingdom_card;
+
+/**
+ * KINGDOM_SIZE
+ * How many kingdom cards a randomly picked kingdom has.
+ */
+pub const KINGDOM_SIZE: usize = 10;
+
+/**
+ * KingdomChoice
+ * How the kingdom for a game should be picked.
+ */
+#[derive(Clone, Debug)]
+pub enum KingdomChoice {
+    // Ten cards picked at random from every registered kingdom card, using the game's seed.
+    Random,
+    // Ten random cards, like Random, but only from kingdoms that meet the constraints.
+    Constrained(KingdomConstraints),
+    // One of the official recommended sets, by name (like "First Game").
+    Preset(String),
+    // Exactly these cards, by name.
+    Cards(Vec<String>)
+}
+
+/**
+ * KingdomPreset
+ * One of the recommended sets of ten from the rulebook.
+ */
+pub struct KingdomPreset {
+    pub name: &'static str,
+    pub cards: [&'static str; KINGDOM_SIZE]
+}
+
+/**
+ * PRESETS
+ * The recommended sets of ten for the base set (2nd edition).
+ */
+pub const PRESETS: [KingdomPreset; 6] = [
+    KingdomPreset {
+        name: "First Game",
+        cards: ["Cellar", "Market", "Merchant", "Militia", "Mine", "Moat", "Remodel", "Smithy", "Village", "Workshop"]
+    },
+    KingdomPreset {
+        name: "Size Distortion",
+        cards: ["Artisan", "Bandit", "Bureaucrat", "Chapel", "Festival", "Gardens", "Sentry", "Throne Room", "Witch", "Workshop"]
+    },
+    KingdomPreset {
+        name: "Deck Top",
+        cards: ["Artisan", "Bureaucrat", "Council Room", "Festival", "Harbinger", "Laboratory", "Moneylender", "Sentry", "Vassal", "Village"]
+    },
+    KingdomPreset {
+        name: "Sleight of Hand",
+        cards: ["Cellar", "Council Room", "Festival", "Gardens", "Library", "Harbinger", "Militia", "Poacher", "Smithy", "Throne Room"]
+    },
+    KingdomPreset {
+        name: "Improvements",
+        cards: ["Artisan", "Cellar", "Market", "Merchant", "Mine", "Moat", "Moneylender", "Poacher", "Remodel", "Witch"]
+    },
+    KingdomPreset {
+        name: "Silver & Gold",
+        cards: ["Bandit", "Bureaucrat", "Chapel", "Harbinger", "Laboratory", "Merchant", "Mine", "Moneylender", "Throne Room", "Vassal"]
+    }
+];
+
+/**
+ * get_preset
+ * Find a recommended set by name, ignoring case.
+ */
+pub fn get_preset(name: &str) -> Option<&'static KingdomPreset> {
+    return PRESETS.iter().find(|p| p.name.eq_ignore_ascii_case(name));
+}
+
+/**
+ * KingdomChoice implementation
+ */
+impl KingdomChoice {
+    /**
+     * describe
+     * A short description of where the kingdom came from, for the game log.
+     */
+    pub fn describe(&self) -> String {
+        match self {
+            KingdomChoice::Random => String::from("Random"),
+            KingdomChoice::Constrained(_) => String::from("Random, constrained"),
+            KingdomChoice::Preset(name) => match get_preset(name) {
+                Some(preset) => String::from(preset.name),
+                None => name.clone()
+            },
+            KingdomChoice::Cards(_) => String::from("Chosen")
+        }
+    }
+
+    /**
+     * choose_cards
+     * Work out the names of the kingdom cards, in cost order (the order of the registered pool).
+     * Random kingdoms pull from the game's rng, so the same seed picks the same kingdom.
+     * Returns an error for an unknown preset, a preset with cards that aren't loaded, a card list with unknown or repeated cards,
+     * or constraints that can't be met.
+     */
+    pub(crate) fn choose_cards(&self, rng: &mut ChaCha8Rng) -> Result<Vec<String>, GameErrors> {
+        let mut cards: Vec<String> = match self {
+            KingdomChoice::Random => {
+                card_registry().get_kingdom_cards().choose_multiple(rng, KINGDOM_SIZE).map(|c| c.get_name().clone()).collect()
+            }
+            KingdomChoice::Constrained(constraints) => constraints.generate(rng)?,
+            KingdomChoice::Preset(name) => match get_preset(name) {
+                Some(preset) => {
+                    // The card data can leave out kingdom cards, so a preset isn't always playable.
+                    let missing: Vec<&str> = preset.cards.iter().copied().filter(|c| !is_kingdom_card(c)).collect();
+                    if !missing.is_empty() {
+                        return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("The {} kingdom needs {}, which aren't loaded.", preset.name, missing.join(", "))}));
+                    }
+                    preset.cards.iter().map(|c| String::from(*c)).collect()
+                }
+                None => {
+                    return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("There is no recommended kingdom called {}.", name)}));
+                }
+            },
+            KingdomChoice::Cards(names) => {
+                if names.is_empty() {
+                    return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: String::from("A kingdom needs at least one card.")}));
+                }
+                for (i, name) in names.iter().enumerate() {
+                    if names[..i].contains(name) {
+                        return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("{} is in the kingdom more than once.", name)}));
+                    }
+                    if !is_kingdom_card(name) {
+                        return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("{} is not a kingdom card.", name)}));
+                    }
+                }
+                names.clone()
+            }
+        };
+
+        // Sort into the pool's order, so the cheapest cards come first on the board.
+        let pool = card_registry().get_kingdom_cards();
+        cards.sort_by_key(|c| pool.iter().position(|k| k.get_name() == c).unwrap_or(pool.len()));
+        return Ok(cards);
+    }
+}
+
+/**
+ * Kingdom tests
+ * Kingdoms picked from seeds, presets and card lists, checked to come out the same every time they're picked.
+ */
+#[cfg(test)]
+mod kingdom_tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn choose(choice: &KingdomChoice, seed: u64) -> Result<Vec<String>, GameErrors> {
+        return choice.choose_cards(&mut ChaCha8Rng::seed_from_u64(seed));
+    }
+
+    #[test]
+    fn the_same_seed_picks_the_same_random_kingdom() {
+        let kingdom = choose(&KingdomChoice::Random, 9).unwrap();
+
+        assert_eq!(kingdom, choose(&KingdomChoice::Random, 9).unwrap());
+        assert_eq!(kingdom.len(), KINGDOM_SIZE);
+        assert!(kingdom.iter().all(|c| is_kingdom_card(c)));
+        assert!(kingdom.iter().enumerate().all(|(i, c)| !kingdom[..i].contains(c)));
+    }
+
+    #[test]
+    fn a_preset_is_its_ten_cards_in_cost_order() {
+        let kingdom = choose(&KingdomChoice::Preset(String::from("first game")), 1).unwrap();
+
+        assert_eq!(kingdom, ["Cellar", "Moat", "Merchant", "Village", "Workshop", "Militia", "Remodel", "Smithy", "Market", "Mine"]);
+        assert_eq!(kingdom, choose(&KingdomChoice::Preset(String::from("First Game")), 2).unwrap());
+    }
+
+    #[test]
+    fn unknown_presets_and_bad_card_lists_are_refused() {
+        assert!(choose(&KingdomChoice::Preset(String::from("Second Game")), 1).is_err());
+        assert!(choose(&KingdomChoice::Cards(Vec::new()), 1).is_err());
+        assert!(choose(&KingdomChoice::Cards(vec![String::from("Smithy"), String::from("Smithy")]), 1).is_err());
+        assert!(choose(&KingdomChoice::Cards(vec![String::from("Copper")]), 1).is_err());
+        assert!(choose(&KingdomChoice::Cards(vec![String::from("Platinum")]), 1).is_err());
+    }
+}
@@ -0,0 +1,359 @@
+/*
+SPUStudnet
+12/15/2024
+kingdom_constraints.rs
+Rules a random kingdom has to follow (a +Buy, enough attacks, a spread of costs, etc.),
+and the randomizer that picks a kingdom meeting all of them.
+*/
+
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::card_manager::card::Card;
+use crate::card_manager::card_registry::{card_registry, CardId};
+use crate::card_manager::card_properties::{ActionEvents, CardTypes};
+use crate::game::game_errors::{GameErrors, UnsatisfiableKingdomError};
+use crate::game::kingdom::KINGDOM_SIZE;
+
+pub use crate::card_manager::card_properties::Expansion;
+
+/**
+ * MAX_ATTEMPTS
+ * How many random kingdoms the randomizer tries before giving up on a set of constraints.
+ */
+const MAX_ATTEMPTS: usize = 1000;
+
+/**
+ * get_expansion
+ * Find which expansion a kingdom card belongs to, as its card definition says.
+ * Returns nothing if there's no such kingdom card.
+ */
+pub fn get_expansion(card_name: &str) -> Option<Expansion> {
+    let card = CardId::named(card_name)?;
+    if !card_registry().get_kingdom_cards().contains(&card) {
+        return None;
+    }
+
+    return Some(card.get_expansion());
+}
+
+/**
+ * CostBand
+ * One step of a cost curve: between min_cards and max_cards of the kingdom must cost from min_cost to max_cost.
+ */
+#[derive(Clone, Debug)]
+pub struct CostBand {
+    pub min_cost: i32,
+    pub max_cost: i32,
+    pub min_cards: usize,
+    pub max_cards: usize
+}
+
+/**
+ * KingdomConstraints
+ * Everything a random kingdom has to satisfy. The default has no constraints at all.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct KingdomConstraints {
+    // At least one card that gives +Buy.
+    pub require_plus_buy: bool,
+    // If there's terminal draw (cards, but no actions), there has to be a village (+2 actions) too.
+    pub village_with_terminal_draw: bool,
+    // At least this many attacks.
+    pub min_attacks: usize,
+    // How many cards fall in each cost range.
+    pub cost_curve: Vec<CostBand>,
+    // Cards that are never picked.
+    pub banned: Vec<String>,
+    // The most cards that can come from each expansion.
+    pub expansion_limits: Vec<(Expansion, usize)>
+}
+
+/**
+ * KingdomCardInfo
+ * What the randomizer needs to know about a kingdom card, read from the card registry.
+ */
+struct KingdomCardInfo {
+    name: String,
+    cost: i32,
+    expansion: Expansion,
+    is_attack: bool,
+    plus_buy: bool,
+    village: bool,
+    terminal_draw: bool
+}
+
+/**
+ * KingdomCardInfo implementation
+ */
+impl KingdomCardInfo {
+    /**
+     * new
+     * Read the card's types, expansion and action properties from the registry.
+     */
+    fn new(card: CardId) -> KingdomCardInfo {
+        let properties = card.get_action_properties();
+        let is_action = card.get_card_types().contains(&CardTypes::Action);
+
+        // Library draws up to seven rather than a set number, so it counts as draw too.
+        let draws = properties.added_cards >= 2 || properties.event == ActionEvents::Library;
+
+        return KingdomCardInfo {
+            name: card.get_name().clone(),
+            cost: card.get_cost(),
+            expansion: card.get_expansion(),
+            is_attack: card.get_card_types().contains(&CardTypes::Attack),
+            plus_buy: properties.added_buys > 0,
+            village: properties.added_actions >= 2,
+            terminal_draw: is_action && draws && properties.added_actions == 0
+        };
+    }
+}
+
+/**
+ * KingdomConstraints implementation
+ */
+impl KingdomConstraints {
+    /**
+     * get_expansion_limit
+     * The most cards the kingdom can take from an expansion (the whole kingdom, if it isn't limited).
+     */
+    fn get_expansion_limit(&self, expansion: Expansion) -> usize {
+        return match self.expansion_limits.iter().find(|(e, _)| *e == expansion) {
+            Some((_, limit)) => *limit,
+            None => KINGDOM_SIZE
+        };
+    }
+
+    /**
+     * check_pool
+     * Look for constraints that no kingdom drawn from the pool could ever meet.
+     * Returns a reason for each one, so they can all be fixed at once.
+     */
+    fn check_pool(&self, pool: &Vec<KingdomCardInfo>) -> Vec<String> {
+        let mut reasons = Vec::new();
+
+        if pool.len() < KINGDOM_SIZE {
+            reasons.push(format!("Only {} kingdom cards are left after the bans, but a kingdom needs {}.", pool.len(), KINGDOM_SIZE));
+        }
+
+        if self.require_plus_buy && !pool.iter().any(|c| c.plus_buy) {
+            reasons.push(String::from("A +Buy card is required, but none are left after the bans."));
+        }
+
+        let attacks = pool.iter().filter(|c| c.is_attack).count();
+        if self.min_attacks > attacks.min(KINGDOM_SIZE) {
+            reasons.push(format!("At least {} attacks are required, but only {} can be picked.", self.min_attacks, attacks.min(KINGDOM_SIZE)));
+        }
+
+        if self.village_with_terminal_draw && !pool.iter().any(|c| c.village) {
+            let without_draw = pool.iter().filter(|c| !c.terminal_draw).count();
+            if without_draw < KINGDOM_SIZE {
+                reasons.push(format!("Terminal draw needs a village, but no villages are left and only {} cards without terminal draw are.", without_draw));
+            }
+        }
+
+        let mut band_minimum = 0;
+        for band in &self.cost_curve {
+            let in_band = pool.iter().filter(|c| c.cost >= band.min_cost && c.cost <= band.max_cost).count();
+            if band.min_cards > band.max_cards {
+                reasons.push(format!("The {}-{} cost band wants at least {} cards but at most {}.", band.min_cost, band.max_cost, band.min_cards, band.max_cards));
+            }
+            if band.min_cards > in_band {
+                reasons.push(format!("The {}-{} cost band wants at least {} cards, but only {} cards cost that much.", band.min_cost, band.max_cost, band.min_cards, in_band));
+            }
+            band_minimum += band.min_cards;
+        }
+        if band_minimum > KINGDOM_SIZE {
+            reasons.push(format!("The cost curve wants at least {} cards, but a kingdom only has {}.", band_minimum, KINGDOM_SIZE));
+        }
+
+        let mut expansions: Vec<Expansion> = Vec::new();
+        for card in pool {
+            if !expansions.contains(&card.expansion) {
+                expansions.push(card.expansion);
+            }
+        }
+        let allowed: usize = expansions.iter()
+            .map(|e| pool.iter().filter(|c| c.expansion == *e).count().min(self.get_expansion_limit(*e)))
+            .sum();
+        if !self.expansion_limits.is_empty() && allowed < KINGDOM_SIZE {
+            reasons.push(format!("The expansion limits only allow {} cards, but a kingdom needs {}.", allowed, KINGDOM_SIZE));
+        }
+
+        return reasons;
+    }
+
+    /**
+     * check_kingdom
+     * Check one picked kingdom against every constraint.
+     * Returns the first constraint it breaks, if any.
+     */
+    fn check_kingdom(&self, kingdom: &Vec<&KingdomCardInfo>) -> Option<String> {
+        if self.require_plus_buy && !kingdom.iter().any(|c| c.plus_buy) {
+            return Some(String::from("there was no +Buy"));
+        }
+
+        if kingdom.iter().filter(|c| c.is_attack).count() < self.min_attacks {
+            return Some(format!("there were fewer than {} attacks", self.min_attacks));
+        }
+
+        if self.village_with_terminal_draw && kingdom.iter().any(|c| c.terminal_draw) && !kingdom.iter().any(|c| c.village) {
+            return Some(String::from("there was terminal draw without a village"));
+        }
+
+        for band in &self.cost_curve {
+            let in_band = kingdom.iter().filter(|c| c.cost >= band.min_cost && c.cost <= band.max_cost).count();
+            if in_band < band.min_cards || in_band > band.max_cards {
+                return Some(format!("the {}-{} cost band didn't have {} to {} cards", band.min_cost, band.max_cost, band.min_cards, band.max_cards));
+            }
+        }
+
+        for (expansion, limit) in &self.expansion_limits {
+            if kingdom.iter().filter(|c| c.expansion == *expansion).count() > *limit {
+                return Some(format!("there were more than {} cards from {:?}", limit, expansion));
+            }
+        }
+
+        return None;
+    }
+
+    /**
+     * generate
+     * Pick a random kingdom that meets every constraint, using the game's rng.
+     * Returns an error listing the reasons when the constraints can't be met,
+     * either because they're impossible with the cards left, or because no random kingdom met them in time.
+     */
+    pub(crate) fn generate(&self, rng: &mut ChaCha8Rng) -> Result<Vec<String>, GameErrors> {
+        let mut reasons = Vec::new();
+        for name in &self.banned {
+            if get_expansion(name).is_none() {
+                reasons.push(format!("{} is not a kingdom card, so it can't be banned.", name));
+            }
+        }
+
+        let pool: Vec<KingdomCardInfo> = card_registry().get_kingdom_cards().iter()
+            .filter(|card| !self.banned.contains(card.get_name()))
+            .map(|card| KingdomCardInfo::new(*card))
+            .collect();
+
+        reasons.append(&mut self.check_pool(&pool));
+        if !reasons.is_empty() {
+            return Err(GameErrors::UnsatisfiableKingdomError(UnsatisfiableKingdomError {reasons: reasons}));
+        }
+
+        // Draw kingdoms until one fits, keeping track of what went wrong most often in case none do.
+        let mut failures: Vec<(String, usize)> = Vec::new();
+        for _ in 0..MAX_ATTEMPTS {
+            let kingdom: Vec<&KingdomCardInfo> = pool.choose_multiple(rng, KINGDOM_SIZE).collect();
+            match self.check_kingdom(&kingdom) {
+                None => {
+                    return Ok(kingdom.iter().map(|c| c.name.clone()).collect());
+                }
+                Some(failure) => match failures.iter_mut().find(|(f, _)| *f == failure) {
+                    Some((_, count)) => *count += 1,
+                    None => failures.push((failure, 1))
+                }
+            }
+        }
+
+        let most_common = failures.iter().max_by_key(|(_, count)| *count).map(|(f, _)| f.clone()).unwrap_or_default();
+        return Err(GameErrors::UnsatisfiableKingdomError(UnsatisfiableKingdomError {
+            reasons: vec![format!("No kingdom met every constraint in {} tries. Most often, {}.", MAX_ATTEMPTS, most_common)]
+        }));
+    }
+}
+
+/**
+ * Constraint tests
+ * Random kingdoms picked under constraints, and constraints that no kingdom of the base set can meet.
+ */
+#[cfg(test)]
+mod constraint_tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // Pick a kingdom under the constraints, returning the reasons they couldn't be met if they weren't.
+    fn generate(constraints: KingdomConstraints) -> Result<Vec<String>, Vec<String>> {
+        return match constraints.generate(&mut ChaCha8Rng::seed_from_u64(5)) {
+            Ok(kingdom) => Ok(kingdom),
+            Err(GameErrors::UnsatisfiableKingdomError(error)) => Err(error.reasons),
+            Err(error) => panic!("Expected the constraints to be unsatisfiable, not {:?}.", error)
+        };
+    }
+
+    fn info(kingdom: &[String]) -> Vec<KingdomCardInfo> {
+        return kingdom.iter().map(|name| KingdomCardInfo::new(CardId::named(name).unwrap())).collect();
+    }
+
+    #[test]
+    fn picked_kingdoms_meet_the_constraints() {
+        let constraints = KingdomConstraints {
+            require_plus_buy: true,
+            village_with_terminal_draw: true,
+            min_attacks: 2,
+            cost_curve: vec![CostBand {min_cost: 5, max_cost: 5, min_cards: 3, max_cards: 4}],
+            banned: vec![String::from("Witch")],
+            ..Default::default()
+        };
+        let kingdom = generate(constraints).expect("The constraints can be met.");
+        let cards = info(&kingdom);
+
+        assert_eq!(kingdom.len(), KINGDOM_SIZE);
+        assert!(!kingdom.contains(&String::from("Witch")));
+        assert!(cards.iter().any(|c| c.plus_buy));
+        assert!(cards.iter().filter(|c| c.is_attack).count() >= 2);
+        assert!(!cards.iter().any(|c| c.terminal_draw) || cards.iter().any(|c| c.village));
+        assert!((3..=4).contains(&cards.iter().filter(|c| c.cost == 5).count()));
+    }
+
+    #[test]
+    fn impossible_constraints_list_every_reason() {
+        let constraints = KingdomConstraints {
+            min_attacks: 5,
+            cost_curve: vec![CostBand {min_cost: 6, max_cost: 6, min_cards: 2, max_cards: 1}],
+            banned: vec![String::from("Copper"), String::from("Festival"), String::from("Market"), String::from("Council Room")],
+            require_plus_buy: true,
+            ..Default::default()
+        };
+
+        assert_eq!(generate(constraints).unwrap_err(), [
+            "Copper is not a kingdom card, so it can't be banned.",
+            "A +Buy card is required, but none are left after the bans.",
+            "At least 5 attacks are required, but only 4 can be picked.",
+            "The 6-6 cost band wants at least 2 cards but at most 1.",
+            "The 6-6 cost band wants at least 2 cards, but only 1 cards cost that much."
+        ]);
+    }
+
+    #[test]
+    fn too_many_bans_leave_too_few_cards() {
+        let names = card_registry().get_kingdom_cards().iter().skip(6).map(|c| c.get_name().clone()).collect();
+        let constraints = KingdomConstraints {banned: names, ..Default::default()};
+
+        assert_eq!(generate(constraints).unwrap_err(), ["Only 6 kingdom cards are left after the bans, but a kingdom needs 10."]);
+    }
+
+    #[test]
+    fn constraints_no_random_kingdom_meets_are_refused() {
+        // Each band can be met on its own, but not both at once.
+        let constraints = KingdomConstraints {
+            cost_curve: vec![
+                CostBand {min_cost: 2, max_cost: 2, min_cards: 2, max_cards: 3},
+                CostBand {min_cost: 2, max_cost: 3, min_cards: 0, max_cards: 0}
+            ],
+            ..Default::default()
+        };
+
+        let reasons = generate(constraints).unwrap_err();
+        assert_eq!(reasons.len(), 1);
+        assert!(reasons[0].starts_with("No kingdom met every constraint in 1000 tries."));
+    }
+
+    #[test]
+    fn expansion_limits_can_rule_out_every_kingdom() {
+        let constraints = KingdomConstraints {expansion_limits: vec![(Expansion::Base, 9)], ..Default::default()};
+
+        assert_eq!(generate(constraints).unwrap_err(), ["The expansion limits only allow 9 cards, but a kingdom needs 10."]);
+    }
+}
@@ -7,141 +7,233 @@ File used to store methods for building piles of cards.
 
 use std::collections::VecDeque;
 use crate::card_manager::card::Card;
-use crate::card_manager::card_builder::*;
+use crate::card_manager::card_properties::CardTypes;
+use crate::card_manager::card_registry::{card_registry, CardId};
 use crate::card_manager::card_structures::Pile;
+use crate::game::game_errors::{GameErrors, InvalidSetupError};
+
+/**
+ * BASE_SET_KINGDOM
+ * The names of all 26 kingdom cards in the base set (2nd edition), in cost order.
+ */
+pub const BASE_SET_KINGDOM: [&str; 26] = [
+    "Cellar", "Chapel", "Moat",
+    "Harbinger", "Merchant", "Vassal", "Village", "Workshop",
+    "Bureaucrat", "Gardens", "Militia", "Moneylender", "Poacher", "Remodel", "Smithy", "Throne Room",
+    "Bandit", "Council Room", "Festival", "Laboratory", "Library", "Market", "Mine", "Sentry", "Witch",
+    "Artisan"
+];
+
+/**
+ * BASIC_SUPPLY
+ * The names of the cards in every game's basic supply: the treasures, the victories, and the curses.
+ */
+pub const BASIC_SUPPLY: [&str; 7] = ["Copper", "Silver", "Gold", "Estate", "Duchy", "Province", "Curse"];
 
 /**
  * build_default_player_deck
  * Build the default player deck, three estates, seven coppers.
  */
-pub(crate) fn build_default_player_deck() -> VecDeque::<Box<dyn Card>> {
+pub(crate) fn build_default_player_deck() -> VecDeque::<CardId> {
     let mut new_default = VecDeque::new();
+    let known = card_registry().get_known_cards();
     for _ in 0..3 {
-        new_default.push_front(build_estate())
+        new_default.push_front(known.estate)
     }
 
     for _ in 0..7 {
-        new_default.push_front(build_copper())
+        new_default.push_front(known.copper)
     }
 
     return new_default;
 }
 
 /**
- * build_treasure_piles
- * Build the default treasure piles:
- * Copper, silver, and gold.
+ * SupplyCounts
+ * How many cards go in each of the basic supply piles.
+ * The official rules change these depending on how many people are playing.
  */
-pub(crate) fn build_treasure_piles() -> Vec<Pile> {
-    let copper_pile = Pile {
-        pile_name: String::from("Copper Pile"),
-        cards_left: 46,
-        card_creator: build_copper
-    };
-
-    let silver_pile = Pile {
-        pile_name: String::from("Silver Pile"),
-        cards_left: 40,
-        card_creator: build_silver
-    };
-
-    let gold_pile = Pile {
-        pile_name: String::from("Gold Pile"),
-        cards_left: 30,
-        card_creator: build_gold
-    };
-
-    return vec![copper_pile, silver_pile, gold_pile];
+#[derive(Clone, Copy)]
+pub struct SupplyCounts {
+    pub coppers: u8,
+    pub silvers: u8,
+    pub golds: u8,
+    pub estates: u8,
+    pub duchies: u8,
+    pub provinces: u8,
+    pub curses: u8
+}
 
+/**
+ * SupplyCounts implementation
+ */
+impl SupplyCounts {
+    /**
+     * for_player_count
+     * Get the pile sizes the rules call for with the given number of players.
+     * Coppers are 60, minus the 7 each player starts with.
+     * Victory piles are 8 with two players, and 12 with more (Provinces go up to 15 and 18 for five and six players).
+     * Curses are 10 per opponent.
+     */
+    pub fn for_player_count(player_count: usize) -> SupplyCounts {
+        let players = player_count as u8;
+
+        let victories = if players <= 2 { 8 } else { 12 };
+        let provinces = match players {
+            0..=2 => 8,
+            3..=4 => 12,
+            5 => 15,
+            _ => 18
+        };
+
+        SupplyCounts {
+            coppers: 60 - 7 * players,
+            silvers: 40,
+            golds: 30,
+            estates: victories,
+            duchies: victories,
+            provinces: provinces,
+            curses: 10 * (players.max(2) - 1)
+        }
+    }
 }
 
 /**
- * build_victory_piles
- * Build the default victory piles:
- * Estates, Duchies, provinces.
+ * build_supply
+ * Build the basic supply (treasure and victory piles) for a game with the given number of players.
+ * Returns the treasures and victories as a tuple, in the order the board stores them.
  */
-pub(crate) fn build_victory_piles() -> Vec<Pile> {
-    let estate_pile = Pile {
-        pile_name: String::from("Estate Pile"),
-        cards_left: 8,
-        card_creator: build_estate
-    };
-
-    let duchy_pile = Pile {
-        pile_name: String::from("Duchy Pile"),
-        cards_left: 8,
-        card_creator: build_duchy
-    };
-
-    let province_pile = Pile {
-        pile_name: String::from("Province Pile"),
-        cards_left: 8,
-        card_creator: build_province
-    };
-
-    return vec![estate_pile, duchy_pile, province_pile];
+pub(crate) fn build_supply(player_count: usize) -> (Vec<Pile>, Vec<Pile>) {
+    let counts = SupplyCounts::for_player_count(player_count);
 
+    return (build_treasure_piles(&counts), build_victory_piles(&counts));
 }
 
 /**
- * Builder for smithy action card.
+ * build_pile
+ * Build a pile of the named card, with the given number of cards in it.
+ * Returns nothing if the card library has no such card.
  */
-pub (crate) fn build_smithy_pile() -> Pile {
-    let smith_pile = Pile {
-        pile_name: String::from("Smithy pile"),
-        cards_left: 10,
-        card_creator: build_smithy
-    };
-
-    return smith_pile;
+pub(crate) fn build_pile(card_name: &str, cards_left: u8) -> Option<Pile> {
+    let card = card_registry().find(card_name)?;
+
+    return Some(Pile {
+        cards_left: cards_left,
+        card: card
+    });
 }
 
+/**
+ * is_kingdom_card
+ * Whether a card can go in the kingdom: any loaded card that isn't part of the basic supply.
+ */
+pub fn is_kingdom_card(card_name: &str) -> bool {
+    return !BASIC_SUPPLY.contains(&card_name) && card_registry().find(card_name).is_some();
+}
 
 /**
- * Builder for village action card.
+ * build_kingdom_pile
+ * Build the pile for a kingdom card, by the card's name.
+ * Kingdom piles have 10 cards, except victory cards (like gardens), which are the same size as the basic victory piles.
+ * Returns nothing if there's no such kingdom card.
  */
-pub (crate) fn build_village_pile() -> Pile {
-    let village_pile = Pile {
-        pile_name: String::from("Village Pile"),
-        cards_left: 10,
-        card_creator: build_village
-    };
-    return village_pile
+pub(crate) fn build_kingdom_pile(card_name: &str, counts: &SupplyCounts) -> Option<Pile> {
+    if !is_kingdom_card(card_name) {
+        return None;
+    }
+
+    let is_victory = card_registry().find(card_name)?.get_card_types().contains(&CardTypes::Victory);
+    return build_pile(card_name, if is_victory { counts.estates } else { 10 });
 }
 
 /**
- * Builder for market action card.
+ * build_kingdom
+ * Build the kingdom piles for the named cards, sized for the number of players.
+ * Returns an error naming the first card that isn't a kingdom card.
  */
-pub (crate) fn build_market_pile() -> Pile {
-    let market_pile = Pile {
-        pile_name: String::from("Market Pile"),
-        cards_left: 10,
-        card_creator: build_market
-    };
-    return market_pile
+pub(crate) fn build_kingdom(card_names: &Vec<String>, player_count: usize) -> Result<Vec<Pile>, GameErrors> {
+    let counts = SupplyCounts::for_player_count(player_count);
+    let mut piles = Vec::new();
+    for name in card_names {
+        match build_kingdom_pile(name, &counts) {
+            Some(pile) => piles.push(pile),
+            None => {
+                return Err(GameErrors::InvalidSetupError(InvalidSetupError {reason: format!("{} is not a kingdom card.", name)}));
+            }
+        }
+    }
+
+    return Ok(piles);
 }
 
 /**
- * Builder for moat action card.
+ * build_treasure_piles
+ * Build the treasure piles:
+ * Copper, silver, and gold.
  */
-pub (crate) fn build_moat_pile() -> Pile {
-    let moat_pile = Pile {
-        pile_name: String::from("Moat Pile"),
-        cards_left: 10,
-        card_creator: build_moat
-    };
-    return moat_pile
+pub(crate) fn build_treasure_piles(counts: &SupplyCounts) -> Vec<Pile> {
+    let known = card_registry().get_known_cards();
+    return vec![
+        Pile { cards_left: counts.coppers, card: known.copper },
+        Pile { cards_left: counts.silvers, card: known.silver },
+        Pile { cards_left: counts.golds, card: known.gold }
+    ];
 }
 
+/**
+ * build_victory_piles
+ * Build the victory piles:
+ * Estates, Duchies, provinces, and the curses (which sit with the victories, as they're scored the same way).
+ */
+pub(crate) fn build_victory_piles(counts: &SupplyCounts) -> Vec<Pile> {
+    let known = card_registry().get_known_cards();
+    return vec![
+        Pile { cards_left: counts.estates, card: known.estate },
+        Pile { cards_left: counts.duchies, card: known.duchy },
+        Pile { cards_left: counts.provinces, card: known.province },
+        Pile { cards_left: counts.curses, card: known.curse }
+    ];
+}
 
 /**
- * Builder for woodcutter action card.
+ * Supply tests
+ * The basic supply, checked against the rulebook's pile sizes for every player count.
  */
-pub (crate) fn build_woodcutter_pile() -> Pile {
-    let woodcutter_pile = Pile {
-        pile_name: String::from("Woodcutter Pile"),
-        cards_left: 10,
-        card_creator: build_woodcutter
-    };
-    return woodcutter_pile
-}
\ No newline at end of file
+#[cfg(test)]
+mod supply_tests {
+    use super::*;
+
+    // The size of each basic pile, by name, for the player count.
+    fn pile_sizes(player_count: usize) -> Vec<(String, u8)> {
+        let (treasures, victories) = build_supply(player_count);
+        return treasures.iter().chain(victories.iter()).map(|p| (p.get_card_name(), p.cards_left)).collect();
+    }
+
+    #[test]
+    fn basic_piles_follow_the_rulebook_for_each_player_count() {
+        // Copper, Silver, Gold, Estate, Duchy, Province, Curse.
+        let expected: [(usize, [u8; 7]); 5] = [
+            (2, [46, 40, 30, 8, 8, 8, 10]),
+            (3, [39, 40, 30, 12, 12, 12, 20]),
+            (4, [32, 40, 30, 12, 12, 12, 30]),
+            (5, [25, 40, 30, 12, 12, 15, 40]),
+            (6, [18, 40, 30, 12, 12, 18, 50])
+        ];
+
+        for (player_count, sizes) in expected {
+            let names = ["Copper", "Silver", "Gold", "Estate", "Duchy", "Province", "Curse"];
+            let expected: Vec<(String, u8)> = names.iter().zip(sizes).map(|(n, s)| (String::from(*n), s)).collect();
+            assert_eq!(pile_sizes(player_count), expected, "with {} players", player_count);
+        }
+    }
+
+    #[test]
+    fn victory_kingdom_piles_match_the_victory_piles() {
+        let two = SupplyCounts::for_player_count(2);
+        let four = SupplyCounts::for_player_count(4);
+
+        assert_eq!(build_kingdom_pile("Gardens", &two).unwrap().cards_left, 8);
+        assert_eq!(build_kingdom_pile("Gardens", &four).unwrap().cards_left, 12);
+        assert_eq!(build_kingdom_pile("Smithy", &four).unwrap().cards_left, 10);
+    }
+}
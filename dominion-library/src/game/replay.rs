@@ -0,0 +1,242 @@
+/*
+SPUStudnet
+12/15/2024
+replay.rs
+Defines replays: everything needed to play a game again exactly as it went (the seed, the kingdom, the seats and every move),
+and re-running them, to any turn, or to the end to check the final scores still come out the same.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::game_command::GameCommand;
+use crate::game::game_errors::{GameErrors, ReplayError};
+use crate::game::game_manager::GameManager;
+use crate::game::game_results::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::kingdom::KingdomChoice;
+use crate::player::player::PlayerStatsInterface;
+use crate::player::seat::Seat;
+
+/**
+ * REPLAY_VERSION
+ * The version of the replay format written by this build.
+ */
+pub const REPLAY_VERSION: u32 = 1;
+
+/**
+ * RecordedMove
+ * One move made in a game, and the seat that made it.
+ * Bots' moves and the answers agents give to decisions are recorded too, so a replay doesn't need the bots to think again.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMove {
+    pub seat: usize,
+    pub command: GameCommand
+}
+
+/**
+ * Replay
+ * A game, written down so it can be played again: the seed, the kingdom (in board order), the seats, and every move in order.
+ * If the game was finished, it also has everyone's final score, to check a re-run against.
+ * Get one from a game with GameManager::get_replay, and attach it to bug reports.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub kingdom: Vec<String>,
+    pub seats: Vec<Seat>,
+    pub moves: Vec<RecordedMove>,
+    // Victory points by seat, if the game was over.
+    pub final_scores: Option<Vec<i32>>
+}
+
+/**
+ * Replay implementation
+ */
+impl Replay {
+    /**
+     * run
+     * Play the whole game again, and return where it ended up.
+     * Returns an error if any move isn't allowed when it comes up (so the engine no longer plays the game the same way).
+     */
+    pub fn run(&self) -> Result<GameState, GameErrors> {
+        return self.run_until(None);
+    }
+
+    /**
+     * state_at_turn
+     * Play the game again up to the start of the given turn (counting every seat's turns, from 1), and return it there.
+     * If the game ended before then, it's returned as it ended.
+     * To carry on playing from there, hand the state to GameManager::from_state.
+     */
+    pub fn state_at_turn(&self, turn: u32) -> Result<GameState, GameErrors> {
+        return self.run_until(Some(turn));
+    }
+
+    /**
+     * verify
+     * Play the whole game again, and check it ends with the same scores it was recorded with.
+     * Returns the final standings if it does, and an error listing every score that changed if it doesn't.
+     */
+    pub fn verify(&self) -> Result<GameResult, GameErrors> {
+        let recorded = match &self.final_scores {
+            Some(scores) => scores,
+            None => return Err(replay_error(String::from("It was recorded before the game was over, so there are no final scores to check."))),
+        };
+
+        let state = self.run()?;
+        let result = match state.turn_controller.get_result() {
+            Some(result) => result.clone(),
+            None => return Err(replay_error(String::from("The game didn't end when replayed."))),
+        };
+
+        let mut differences = Vec::new();
+        for (seat, player) in state.players.iter().enumerate() {
+            let replayed = player.get_victory_points();
+            match recorded.get(seat) {
+                Some(score) if *score == replayed => {}
+                Some(score) => differences.push(format!("{} (seat {}) scored {} when recorded, but {} when replayed.", player.name, seat, score, replayed)),
+                None => differences.push(format!("{} (seat {}) has no recorded score.", player.name, seat))
+            }
+        }
+        if !differences.is_empty() {
+            return Err(replay_error(differences.join(" ")));
+        }
+
+        return Ok(result);
+    }
+
+    /**
+     * to_json / from_json
+     * Write a replay out as JSON, or read one back in.
+     */
+    pub fn to_json(&self) -> Result<String, GameErrors> {
+        return serde_json::to_string_pretty(self).map_err(|e| replay_error(e.to_string()));
+    }
+
+    pub fn from_json(text: &str) -> Result<Replay, GameErrors> {
+        let replay: Replay = serde_json::from_str(text).map_err(|e| replay_error(e.to_string()))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(replay_error(format!("It was written in format version {}, but this version of the game reads version {}.", replay.version, REPLAY_VERSION)));
+        }
+        return Ok(replay);
+    }
+
+    /**
+     * run_until
+     * Play the game again, stopping at the start of the given turn (or going to the end, if there isn't one).
+     * Every seat is played as a human, so nothing moves unless a recorded move says so.
+     * The seats get their real kinds (and strategies) back before the state is returned.
+     */
+    fn run_until(&self, turn: Option<u32>) -> Result<GameState, GameErrors> {
+        let seats = self.seats.iter().map(|s| Seat::human(&s.name)).collect();
+        let mut game = GameManager::with_kingdom(seats, Some(self.seed), KingdomChoice::Cards(self.kingdom.clone()))?;
+
+        for (index, m) in self.moves.iter().enumerate() {
+            if let Some(turn) = turn {
+                if game.get_turn_controller().get_turn_number() >= turn {
+                    break;
+                }
+            }
+            if let Err(e) = game.apply(m.seat, m.command.clone()) {
+                return Err(replay_error(format!("Move {} (seat {}: {:?}) wasn't allowed: {:?}", index, m.seat, m.command, e)));
+            }
+        }
+
+        let mut state = game.get_state().clone();
+        for (player, seat) in state.players.iter_mut().zip(self.seats.iter()) {
+            player.kind = seat.kind;
+            player.strategy = seat.strategy;
+        }
+        return Ok(state);
+    }
+}
+
+/**
+ * Helper: wrap up a problem with a replay as a game error.
+ */
+fn replay_error(reason: String) -> GameErrors {
+    return GameErrors::ReplayError(ReplayError { reason: reason });
+}
+
+/**
+ * Replay tests
+ * A two-player game between humans buying Smithies and money, recorded, then replayed and checked against its final scores.
+ * The seats try to play every action in their hand, so the game is sure to have plays refused for want of an action.
+ */
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+    use crate::card_manager::card::Card;
+    use crate::card_manager::card_properties::CardTypes;
+    use crate::card_manager::card_registry::CardId;
+    use crate::player::phases::PlayerPhases;
+
+    const SEED: u64 = 11;
+
+    // Play the current seat's turn, and return how many of its plays were refused.
+    fn play_turn(gm: &mut GameManager) -> usize {
+        let seat = gm.get_current_player_index();
+        let mut refused = 0;
+
+        // Try every action in the hand, left to right, whether or not there's an action left to play it with.
+        let mut next = 0;
+        loop {
+            let hand = &gm.get_state().players[seat].hand;
+            let action = (next..hand.len()).find(|i| hand[*i].get_card_types().contains(&CardTypes::Action));
+            let index = match action {
+                Some(index) => index,
+                None => break
+            };
+            if gm.apply(seat, GameCommand::PlayCard { hand_index: index }).is_err() {
+                refused += 1;
+                next = index + 1;
+            }
+        }
+
+        while gm.get_state().players[seat].phase != PlayerPhases::Buy {
+            gm.apply(seat, GameCommand::EndPhase).expect("The seat should be able to move on to buying.");
+        }
+        if gm.legal_moves(seat).contains(&GameCommand::PlayAllTreasures) {
+            gm.apply(seat, GameCommand::PlayAllTreasures).expect("The seat has treasures to play.");
+        }
+
+        let buy = match gm.get_state().players[seat].buy_power {
+            8.. => "Province",
+            6..=7 => "Gold",
+            4..=5 => "Smithy",
+            3 => "Silver",
+            _ => ""
+        };
+        let pile = CardId::named(buy).and_then(|card| gm.get_state().board.find_pile(card));
+        if let Some((index, set)) = pile {
+            let _ = gm.apply(seat, GameCommand::Buy { set: set, index: index });
+        }
+
+        gm.apply(seat, GameCommand::EndTurn).expect("The seat should be able to end its turn.");
+        return refused;
+    }
+
+    #[test]
+    fn a_game_with_refused_plays_replays_to_the_same_scores() {
+        let seats = vec![Seat::human("Alice"), Seat::human("Bob")];
+        let kingdom = KingdomChoice::Cards(vec![String::from("Moat"), String::from("Smithy")]);
+        let mut gm = GameManager::with_kingdom(seats, Some(SEED), kingdom).expect("The test game should set up.");
+
+        let mut refused = 0;
+        for _ in 0..200 {
+            if gm.check_ending().is_some() {
+                break;
+            }
+            refused += play_turn(&mut gm);
+        }
+        assert!(gm.check_ending().is_some(), "The game should have ended.");
+        assert!(refused > 0, "Some plays should have been refused.");
+
+        let replay = gm.get_replay().expect("The game has a history.");
+        let result = replay.verify().expect("The replay should end with the recorded scores.");
+        let scores = |result: &GameResult| result.standings.iter().map(|s| (s.seat, s.victory_points)).collect::<Vec<_>>();
+        assert_eq!(scores(&result), scores(&gm.check_ending().unwrap()));
+    }
+}
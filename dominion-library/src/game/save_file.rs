@@ -0,0 +1,229 @@
+/*
+SPUStudnet
+12/15/2024
+save_file.rs
+Defines the save file: a game's whole state written out as versioned JSON, so it can be stopped and picked up again later.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::game_errors::{GameErrors, SaveFileError};
+use crate::game::game_state::GameState;
+
+/**
+ * SAVE_VERSION
+ * The version of the save format written by this build.
+ * Bump it whenever GameState (or anything in it) changes shape, so old saves are turned away with a clear message
+ * instead of loading wrong.
+ */
+pub const SAVE_VERSION: u32 = 2;
+
+/**
+ * SaveFile
+ * What's written to disk: the format version, then the state.
+ * Everything is saved, down to the order of every deck and where each player's shuffles have got to,
+ * so a loaded game carries on exactly as the saved one would have.
+ */
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    state: &'a GameState
+}
+
+/**
+ * SaveVersion
+ * The version of a save being read back in.
+ * It's read before anything else, so a save from another version never gets as far as the state.
+ */
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32
+}
+
+/**
+ * LoadedSave
+ * A save being read back in, once its version has been checked.
+ */
+#[derive(Deserialize)]
+struct LoadedSave {
+    state: GameState
+}
+
+/**
+ * save_to_string
+ * Write a game's state out as a save.
+ */
+pub fn save_to_string(state: &GameState) -> Result<String, GameErrors> {
+    let save = SaveFile { version: SAVE_VERSION, state: state };
+    return serde_json::to_string(&save).map_err(|e| save_error("(in memory)", e.to_string()));
+}
+
+/**
+ * load_from_string
+ * Read a game's state back in from a save.
+ * Returns an error if the save isn't valid, was written by another version, or holds a game that couldn't happen
+ * (like one pointing at a seat that isn't at the table).
+ */
+pub fn load_from_string(text: &str, source: &str) -> Result<GameState, GameErrors> {
+    let version: SaveVersion = serde_json::from_str(text).map_err(|e| save_error(source, e.to_string()))?;
+    if version.version != SAVE_VERSION {
+        return Err(save_error(source, format!("it was saved in format version {}, but this version of the game reads version {}.", version.version, SAVE_VERSION)));
+    }
+
+    let loaded: LoadedSave = serde_json::from_str(text).map_err(|e| save_error(source, e.to_string()))?;
+    let seats = loaded.state.players.len();
+    if !(2..=6).contains(&seats) {
+        return Err(save_error(source, format!("it has {} seats, but Dominion is played by 2 to 6 players.", seats)));
+    }
+    if loaded.state.players.iter().enumerate().any(|(seat, p)| p.seat != seat) {
+        return Err(save_error(source, String::from("its players are out of seat order.")));
+    }
+    check_seats(&loaded.state, source)?;
+
+    return Ok(loaded.state);
+}
+
+/**
+ * check_seats
+ * Make sure every seat the state points to is at the table: whose turn it is, who a decision is waiting on,
+ * who each card effect is for (and who it's hitting), the final standings, and who made each move.
+ * A seat that isn't there would crash the game as soon as it was looked up.
+ */
+fn check_seats(state: &GameState, source: &str) -> Result<(), GameErrors> {
+    let seats = state.players.len();
+    let mut problems = Vec::new();
+    let mut check = |what: String, seat: usize| {
+        if seat >= seats {
+            problems.push(format!("{}: seat {}, but there are only {} seats.", what, seat, seats));
+        }
+    };
+
+    check(String::from("whose turn it is"), state.turn_controller.get_current_player());
+    if let Some(pending) = &state.pending_decision {
+        check(String::from("who the pending decision is waiting on"), pending.seat);
+    }
+    for (index, frame) in state.effects.iter().enumerate() {
+        check(format!("who card effect {} is for", index), frame.seat);
+        if let Some(target) = frame.target {
+            check(format!("who card effect {} is hitting", index), target);
+        }
+        for seat in frame.protected.iter() {
+            check(format!("who is protected from card effect {}", index), *seat);
+        }
+    }
+    if let Some(result) = state.turn_controller.get_result() {
+        for standing in result.standings.iter() {
+            check(format!("{}'s standing", standing.name), standing.seat);
+        }
+    }
+    if let Some(history) = &state.history {
+        for (index, m) in history.iter().enumerate() {
+            check(format!("who made move {}", index), m.seat);
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(save_error(source, problems.join(" ")));
+    }
+    return Ok(());
+}
+
+/**
+ * save_to_file
+ * Write a game's state out as a save, to the given file (replacing it, if it's already there).
+ */
+pub fn save_to_file(state: &GameState, path: &str) -> Result<(), GameErrors> {
+    let text = save_to_string(state)?;
+    return std::fs::write(path, text).map_err(|e| save_error(path, e.to_string()));
+}
+
+/**
+ * load_from_file
+ * Read a game's state back in from a save file.
+ */
+pub fn load_from_file(path: &str) -> Result<GameState, GameErrors> {
+    let text = std::fs::read_to_string(path).map_err(|e| save_error(path, e.to_string()))?;
+    return load_from_string(&text, path);
+}
+
+/**
+ * Helper: wrap up a problem with a save as a game error.
+ */
+fn save_error(source: &str, reason: String) -> GameErrors {
+    return GameErrors::SaveFileError(SaveFileError { source: source.to_string(), reason: reason });
+}
+
+/**
+ * Save tests
+ * Games saved partway through and loaded back in, and saves that are turned away.
+ */
+#[cfg(test)]
+mod save_tests {
+    use super::*;
+    use serde_json::Value;
+
+    use crate::game::game_command::GameCommand;
+    use crate::game::game_manager::GameManager;
+    use crate::player::seat::Seat;
+
+    // A game a couple of turns in, saved.
+    fn saved_game() -> (GameManager, String) {
+        let mut gm = GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob"), Seat::human("Carol")], Some(8)).unwrap();
+        for _ in 0..2 {
+            let seat = gm.get_current_player_index();
+            gm.apply(seat, GameCommand::EndTurn).unwrap();
+        }
+        let text = save_to_string(gm.get_state()).unwrap();
+        return (gm, text);
+    }
+
+    // Load the save, expecting it to be turned away, and return why.
+    fn refusal(text: &str) -> String {
+        return match load_from_string(text, "test save") {
+            Ok(_) => panic!("The save should have been turned away."),
+            Err(GameErrors::SaveFileError(error)) => error.reason,
+            Err(error) => panic!("Expected a save file error, not {:?}.", error)
+        };
+    }
+
+    // The save with one of its values changed.
+    fn edited(text: &str, pointer: &str, value: Value) -> String {
+        let mut save: Value = serde_json::from_str(text).unwrap();
+        *save.pointer_mut(pointer).expect("The save should have the value being changed.") = value;
+        return save.to_string();
+    }
+
+    #[test]
+    fn a_loaded_game_carries_on_exactly_as_the_saved_one() {
+        let (mut gm, text) = saved_game();
+        let mut loaded = GameManager::from_state(load_from_string(&text, "test save").unwrap());
+        assert_eq!(save_to_string(loaded.get_state()).unwrap(), text);
+
+        // Both games shuffle and draw the same cards from here on.
+        for _ in 0..6 {
+            let seat = gm.get_current_player_index();
+            gm.apply(seat, GameCommand::EndTurn).unwrap();
+            loaded.apply(seat, GameCommand::EndTurn).unwrap();
+        }
+        assert_eq!(save_to_string(loaded.get_state()).unwrap(), save_to_string(gm.get_state()).unwrap());
+    }
+
+    #[test]
+    fn saves_from_other_versions_are_refused() {
+        let (_, text) = saved_game();
+
+        assert_eq!(refusal(&edited(&text, "/version", Value::from(1))),
+                   format!("it was saved in format version 1, but this version of the game reads version {}.", SAVE_VERSION));
+    }
+
+    #[test]
+    fn saves_pointing_at_missing_seats_are_refused() {
+        let (_, text) = saved_game();
+
+        assert_eq!(refusal(&edited(&text, "/state/history/0/seat", Value::from(3))), "who made move 0: seat 3, but there are only 3 seats.");
+
+        let mut save: Value = serde_json::from_str(&text).unwrap();
+        save.pointer_mut("/state/players").unwrap().as_array_mut().unwrap().truncate(1);
+        assert_eq!(refusal(&save.to_string()), "it has 1 seats, but Dominion is played by 2 to 6 players.");
+    }
+}
@@ -0,0 +1,210 @@
+/*
+SPUStudnet
+12/15/2024
+turn_controller.rs
+Defines the turn controller: the one place that decides which phase comes next, whose turn it is,
+and when the game is over.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::card_manager::card::Card;
+use crate::game::board::Board;
+use crate::game::game_errors::{GameErrors, InvalidActionError};
+use crate::game::game_event::GameEvent;
+use crate::game::game_results::GameResult;
+use crate::player::phases::PlayerPhases;
+use crate::player::player::{Player, PlayerStatsInterface};
+
+/**
+ * TurnController
+ * Owns the phase order of every turn:
+ * Start -> Action -> Buy -> Night (only if the player has night cards) -> Cleanup -> hand-off to the next seat.
+ * The end of the game is checked after every cleanup, before the next seat starts.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TurnController {
+    current_player: usize,
+    turn_number: u32,
+    result: Option<GameResult>
+}
+
+/**
+ * TurnController implementation
+ */
+impl TurnController {
+    /**
+     * Create a new turn controller, with the first seat up and no turns played.
+     */
+    pub(crate) fn new() -> TurnController {
+        TurnController {
+            current_player: 0,
+            turn_number: 0,
+            result: None
+        }
+    }
+
+    /**
+     * get_current_player
+     * The seat whose turn it is.
+     */
+    pub fn get_current_player(&self) -> usize {
+        return self.current_player;
+    }
+
+    /**
+     * get_turn_number
+     * How many turns have been started, across every seat.
+     */
+    pub fn get_turn_number(&self) -> u32 {
+        return self.turn_number;
+    }
+
+    /**
+     * get_result
+     * The final standings, if the game is over.
+     */
+    pub fn get_result(&self) -> Option<&GameResult> {
+        return self.result.as_ref();
+    }
+
+    /**
+     * is_game_over
+     * Has the end condition been met?
+     */
+    pub fn is_game_over(&self) -> bool {
+        return self.result.is_some();
+    }
+
+    /**
+     * start_turn
+     * Start the current seat's turn, moving them from idle through the start of their turn into their action phase.
+     */
+    pub(crate) fn start_turn(&mut self, players: &mut [Player]) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        self.turn_number += 1;
+        events.push(GameEvent::TurnStarted { seat: self.current_player, turn: self.turn_number });
+
+        // Nothing happens at the start of a turn yet, so go straight on into actions.
+        TurnController::set_phase(players, self.current_player, PlayerPhases::Start, &mut events);
+        TurnController::set_phase(players, self.current_player, PlayerPhases::Action, &mut events);
+
+        return events;
+    }
+
+    /**
+     * advance_phase
+     * Move the current player on to their next phase.
+     * Leaving the buy (or night) phase runs cleanup, checks for the end of the game, and hands the turn to the next seat.
+     */
+    pub(crate) fn advance_phase(&mut self, players: &mut [Player], board: &Board) -> Result<Vec<GameEvent>, GameErrors> {
+        self.check_not_over()?;
+
+        let mut events = Vec::new();
+        let seat = self.current_player;
+        match TurnController::next_phase(&players[seat]) {
+            PlayerPhases::Cleanup => {
+                self.finish_turn(players, board, &mut events);
+            }
+            PlayerPhases::Idle | PlayerPhases::Start | PlayerPhases::Never => {
+                return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("There is no phase to advance to right now.")}));
+            }
+            next => {
+                TurnController::set_phase(players, seat, next, &mut events);
+            }
+        }
+
+        return Ok(events);
+    }
+
+    /**
+     * end_turn
+     * End the current player's turn from whatever phase they're in, skipping the rest of it.
+     */
+    pub(crate) fn end_turn(&mut self, players: &mut [Player], board: &Board) -> Result<Vec<GameEvent>, GameErrors> {
+        self.check_not_over()?;
+
+        let mut events = Vec::new();
+        self.finish_turn(players, board, &mut events);
+
+        return Ok(events);
+    }
+
+    /**
+     * finish_turn
+     * Run cleanup, then either end the game or hand off to the next seat.
+     */
+    fn finish_turn(&mut self, players: &mut [Player], board: &Board, events: &mut Vec<GameEvent>) {
+        let seat = self.current_player;
+
+        // Cleanup: discard everything, draw a new hand.
+        TurnController::set_phase(players, seat, PlayerPhases::Cleanup, events);
+        players[seat].cleanup_and_draw();
+        events.append(&mut players[seat].events);
+        players[seat].turns_taken += 1;
+        TurnController::set_phase(players, seat, PlayerPhases::Idle, events);
+        events.push(GameEvent::TurnEnded { seat: seat });
+
+        // The game only ends between turns, so this is the moment to check.
+        if board.check_ending(players.len()) {
+            let mut scores = Vec::new();
+            for (seat, player) in players.iter().enumerate() {
+                scores.push((seat, player.name.clone(), player.get_victory_points(), player.turns_taken));
+            }
+
+            let result = GameResult::rank(scores);
+            events.push(GameEvent::GameEnded(result.clone()));
+            self.result = Some(result);
+            return;
+        }
+
+        // Hand off to the next seat, wrapping back around to the first.
+        self.current_player = (seat + 1) % players.len();
+        events.append(&mut self.start_turn(players));
+    }
+
+    /**
+     * next_phase
+     * The phase that comes after the one the player is in.
+     * Night is skipped if the player has no cards they could play during it.
+     */
+    fn next_phase(player: &Player) -> PlayerPhases {
+        match player.phase {
+            PlayerPhases::Idle => PlayerPhases::Start,
+            PlayerPhases::Start => PlayerPhases::Action,
+            PlayerPhases::Action => PlayerPhases::Buy,
+            PlayerPhases::Buy => {
+                if player.hand.iter().any(|c| c.get_playing_phase() == &PlayerPhases::Night) {
+                    PlayerPhases::Night
+                }
+                else {
+                    PlayerPhases::Cleanup
+                }
+            }
+            PlayerPhases::Night => PlayerPhases::Cleanup,
+            PlayerPhases::Cleanup => PlayerPhases::Idle,
+            PlayerPhases::Never => PlayerPhases::Never
+        }
+    }
+
+    /**
+     * set_phase
+     * Move a player into a phase, and record the transition.
+     */
+    fn set_phase(players: &mut [Player], seat: usize, to: PlayerPhases, events: &mut Vec<GameEvent>) {
+        let from = players[seat].phase;
+        players[seat].phase = to;
+        events.push(GameEvent::PhaseChanged { seat: seat, from: from, to: to });
+    }
+
+    /**
+     * check_not_over
+     * Returns an error if the game is already over, as no more moves can be made.
+     */
+    fn check_not_over(&self) -> Result<(), GameErrors> {
+        if self.is_game_over() {
+            return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("The game is over.")}));
+        }
+        return Ok(());
+    }
+}
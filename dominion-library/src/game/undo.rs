@@ -0,0 +1,211 @@
+/*
+SPUStudnet
+12/15/2024
+undo.rs
+Defines undo and redo: taking back moves that didn't show anybody anything new, and making them again.
+*/
+
+use crate::game::game_errors::{GameErrors, InvalidActionError};
+use crate::game::game_event::GameEvent;
+use crate::game::game_state::GameState;
+
+/**
+ * UndoStep
+ * A move that can be taken back (or made again), as the state on the other side of it, and the seat that made it.
+ */
+struct UndoStep {
+    seat: usize,
+    state: GameState
+}
+
+/**
+ * UndoStack struct
+ * The moves that can be taken back, and the ones that have been and can be made again.
+ * Only moves that didn't reveal anything can be taken back: once a move shuffles, draws, reveals or looks at a card,
+ * or someone else makes a decision, the stack is emptied, and that's the undo barrier.
+ * Everything on the stack was made by the same seat, as another seat moving is a barrier too.
+ */
+pub(crate) struct UndoStack {
+    undo: Vec<UndoStep>,
+    redo: Vec<UndoStep>
+}
+
+/**
+ * UndoStack implementation
+ */
+impl UndoStack {
+    /**
+     * Create an empty stack, with nothing to undo or redo.
+     */
+    pub(crate) fn new() -> UndoStack {
+        return UndoStack { undo: Vec::new(), redo: Vec::new() };
+    }
+
+    /**
+     * record
+     * A move was made by the given seat, from the state before it.
+     * Making a move means anything undone can't be redone any more.
+     * If the move revealed something (or another seat was the last to move), it's a new barrier, and nothing before it can be undone.
+     */
+    pub(crate) fn record(&mut self, seat: usize, before: GameState, revealed: bool) {
+        self.redo.clear();
+        if revealed {
+            self.undo.clear();
+            return;
+        }
+        if self.undo.last().is_some_and(|step| step.seat != seat) {
+            self.undo.clear();
+        }
+        self.undo.push(UndoStep { seat: seat, state: before });
+    }
+
+    /**
+     * undo_depth / redo_depth
+     * How many of the seat's moves can be taken back before reaching the undo barrier, and how many taken back can be made again.
+     */
+    pub(crate) fn undo_depth(&self, seat: usize) -> usize {
+        return match self.undo.last() {
+            Some(step) if step.seat == seat => self.undo.len(),
+            _ => 0
+        };
+    }
+
+    pub(crate) fn redo_depth(&self, seat: usize) -> usize {
+        return match self.redo.last() {
+            Some(step) if step.seat == seat => self.redo.len(),
+            _ => 0
+        };
+    }
+
+    /**
+     * undo
+     * Take back the seat's last move: puts the state from before it in place of the current one, which is kept to redo.
+     */
+    pub(crate) fn undo(&mut self, seat: usize, current: &mut GameState) -> Result<(), GameErrors> {
+        if self.undo_depth(seat) == 0 {
+            return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("There's nothing to undo since the last shuffle, draw or decision.")}));
+        }
+        let step = self.undo.pop().unwrap();
+        self.redo.push(UndoStep { seat: seat, state: std::mem::replace(current, step.state) });
+        return Ok(());
+    }
+
+    /**
+     * redo
+     * Make the seat's last undone move again: puts the state from after it in place of the current one, which is kept to undo.
+     */
+    pub(crate) fn redo(&mut self, seat: usize, current: &mut GameState) -> Result<(), GameErrors> {
+        if self.redo_depth(seat) == 0 {
+            return Err(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("There's nothing to redo.")}));
+        }
+        let step = self.redo.pop().unwrap();
+        self.undo.push(UndoStep { seat: seat, state: std::mem::replace(current, step.state) });
+        return Ok(());
+    }
+}
+
+/**
+ * reveals_information
+ * Whether a move by the given seat, from before to after (with the events it caused), showed anybody something they didn't know,
+ * so taking it back would let them play differently knowing it.
+ * That's any shuffle, draw or reveal, any card taken off the top of a deck, the turn passing or the game ending,
+ * and any decision made by another seat.
+ */
+pub(crate) fn reveals_information(seat: usize, before: &GameState, after: &GameState, events: &[GameEvent]) -> bool {
+    let revealing_event = events.iter().any(|e| matches!(e,
+        GameEvent::Shuffled { .. } | GameEvent::Drew { .. } | GameEvent::CardRevealed { .. } |
+        GameEvent::TurnStarted { .. } | GameEvent::TurnEnded { .. } | GameEvent::GameEnded(_)));
+    if revealing_event {
+        return true;
+    }
+
+    // Cards can come off a deck without being drawn or revealed (to be discarded or trashed), and they're seen either way.
+    if before.players.iter().zip(after.players.iter()).any(|(b, a)| a.deck.len() < b.deck.len()) {
+        return true;
+    }
+
+    // Other seats' answers are in the history. Forks don't keep one, so there, any change to another seat's cards counts.
+    return match (&before.history, &after.history) {
+        (Some(b), Some(a)) => a[b.len().min(a.len())..].iter().any(|m| m.seat != seat),
+        _ => before.players.iter().zip(after.players.iter()).any(|(b, a)| {
+            a.seat != seat && (a.hand != b.hand || a.discard.len() != b.discard.len())
+        })
+    };
+}
+
+/**
+ * Undo tests
+ * Moves taken back and made again, up to the undo barrier and no further.
+ */
+#[cfg(test)]
+mod undo_tests {
+    use crate::card_manager::card_registry::CardId;
+    use crate::game::game_command::GameCommand;
+    use crate::game::game_manager::GameManager;
+    use crate::game::save_file::save_to_string;
+    use crate::player::seat::Seat;
+
+    // A two-player game with the first seat holding the given hand.
+    fn game_with_hand(cards: &[&str]) -> GameManager {
+        let gm = GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(4)).unwrap();
+        let mut state = gm.get_state().clone();
+        state.players[0].hand = cards.iter().map(|c| CardId::named(c).unwrap()).collect();
+        return GameManager::from_state(state);
+    }
+
+    // The whole state, to compare games by.
+    fn snapshot(gm: &GameManager) -> String {
+        return save_to_string(gm.get_state()).unwrap();
+    }
+
+    #[test]
+    fn moves_that_reveal_nothing_can_be_undone_and_redone() {
+        let mut gm = game_with_hand(&["Copper", "Copper", "Copper", "Estate", "Estate"]);
+        let start = snapshot(&gm);
+
+        gm.apply(0, GameCommand::EndPhase).unwrap();
+        gm.apply(0, GameCommand::PlayAllTreasures).unwrap();
+        let end = snapshot(&gm);
+        assert_eq!(gm.undo_depth(0), 2);
+        assert_eq!(gm.undo_depth(1), 0);
+
+        gm.undo(0).unwrap();
+        gm.undo(0).unwrap();
+        assert_eq!(snapshot(&gm), start);
+        assert!(gm.undo(0).is_err());
+
+        assert_eq!(gm.redo_depth(0), 2);
+        gm.redo(0).unwrap();
+        gm.redo(0).unwrap();
+        assert_eq!(snapshot(&gm), end);
+        assert!(gm.redo(0).is_err());
+    }
+
+    #[test]
+    fn undo_stops_at_a_draw() {
+        let mut gm = game_with_hand(&["Smithy", "Copper", "Copper", "Copper", "Estate"]);
+
+        gm.apply(0, GameCommand::PlayCard { hand_index: 0 }).unwrap();
+        let drawn = snapshot(&gm);
+        assert_eq!(gm.undo_depth(0), 0);
+
+        gm.apply(0, GameCommand::EndPhase).unwrap();
+        gm.apply(0, GameCommand::PlayAllTreasures).unwrap();
+        gm.undo(0).unwrap();
+        gm.undo(0).unwrap();
+        assert_eq!(snapshot(&gm), drawn);
+        assert!(gm.undo(0).is_err());
+    }
+
+    #[test]
+    fn undo_stops_at_the_end_of_the_turn() {
+        let mut gm = game_with_hand(&["Copper", "Copper", "Copper", "Estate", "Estate"]);
+
+        gm.apply(0, GameCommand::EndPhase).unwrap();
+        gm.apply(0, GameCommand::EndTurn).unwrap();
+        assert_eq!(gm.undo_depth(0), 0);
+        assert_eq!(gm.undo_depth(1), 0);
+        assert!(gm.undo(0).is_err());
+        assert!(gm.undo(1).is_err());
+    }
+}
@@ -6,4 +6,7 @@ Reference module, required by rust to reference modules in folder structures.
 */
 
 pub mod player;
-pub mod phases;
\ No newline at end of file
+pub mod phases;
+pub mod seat;
+pub mod agent;
+pub mod strategy;
\ No newline at end of file
@@ -0,0 +1,331 @@
+/*
+SPUStudnet
+12/15/2024
+agent.rs
+Defines the decisions a card can ask a player to make, and the Agent trait the game asks them through.
+The TUI, the built-in bots, and scripted test players each answer decisions through an Agent.
+*/
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card_manager::card::Card;
+use crate::card_manager::card_properties::CardTypes;
+use crate::card_manager::card_registry::CardId;
+use crate::game::board::CardSet;
+
+/**
+ * SupplyChoice
+ * One supply pile a player is allowed to pick in a ChooseSupplyPile decision.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SupplyChoice {
+    pub set: CardSet,
+    pub index: usize,
+    pub name: String,
+    pub cost: i32
+}
+
+/**
+ * DecisionRequest
+ * A choice the game needs a player to make before a card's effect can carry on.
+ * Every request carries a prompt that can be shown to a person as-is.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DecisionRequest {
+    // Pick between min and max cards from your hand (optionally, only cards of one type).
+    // Answered with Decision::Cards, holding hand indices.
+    ChooseCardsFromHand { prompt: String, min: usize, max: usize, card_type: Option<CardTypes> },
+    // Pick between min and max of the listed cards (revealed cards, your discard, etc.)
+    // Answered with Decision::Cards, holding indices into the list.
+    ChooseCards { prompt: String, cards: Vec<String>, min: usize, max: usize },
+    // Pick one of the listed supply piles. The engine has already filtered out anything too expensive or empty.
+    // Answered with Decision::SupplyPile, holding an index into the choices (or None, if the choice is optional).
+    ChooseSupplyPile { prompt: String, max_cost: i32, choices: Vec<SupplyChoice>, optional: bool },
+    // Answer yes or no.
+    // Answered with Decision::YesNo.
+    YesNo { prompt: String },
+    // Put the listed cards in order, first being the top.
+    // Answered with Decision::Order, holding every index into the list exactly once.
+    OrderCards { prompt: String, cards: Vec<String> }
+}
+
+/**
+ * Decision
+ * An answer to a DecisionRequest.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Decision {
+    Cards(Vec<usize>),
+    SupplyPile(Option<usize>),
+    YesNo(bool),
+    Order(Vec<usize>)
+}
+
+/**
+ * DecisionView
+ * What an agent gets to look at when making a decision: which seat it is, and that seat's hand.
+ */
+pub struct DecisionView<'a> {
+    pub seat: usize,
+    pub hand: &'a Vec<CardId>
+}
+
+/**
+ * PendingDecision
+ * A decision the game is waiting on, and the seat that has to make it.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingDecision {
+    pub seat: usize,
+    pub request: DecisionRequest
+}
+
+/**
+ * Agent trait
+ * Anything that can make decisions for a seat.
+ * Returning Some answers the request straight away.
+ * Returning None defers it: the game pauses with the request pending until a frontend calls GameManager::resolve_decision.
+ * Agents have to be Send and Sync, as the game holding them can be moved to (and shared between) other threads.
+ */
+pub trait Agent: Send + Sync {
+    fn decide(&mut self, view: &DecisionView, request: &DecisionRequest) -> Option<Decision>;
+}
+
+/**
+ * DecisionRequest implementation
+ */
+impl DecisionRequest {
+    /**
+     * get_prompt
+     * The text to show whoever is making the decision.
+     */
+    pub fn get_prompt(&self) -> &String {
+        match self {
+            DecisionRequest::ChooseCardsFromHand { prompt, .. } => prompt,
+            DecisionRequest::ChooseCards { prompt, .. } => prompt,
+            DecisionRequest::ChooseSupplyPile { prompt, .. } => prompt,
+            DecisionRequest::YesNo { prompt } => prompt,
+            DecisionRequest::OrderCards { prompt, .. } => prompt
+        }
+    }
+
+    /**
+     * is_valid
+     * Checks whether a decision is an acceptable answer to this request, given the deciding player's hand.
+     */
+    pub fn is_valid(&self, decision: &Decision, hand: &[CardId]) -> bool {
+        match (self, decision) {
+            (DecisionRequest::ChooseCardsFromHand { min, max, card_type, .. }, Decision::Cards(picks)) => {
+                if !DecisionRequest::distinct_in_range(picks, hand.len()) || picks.len() < *min || picks.len() > *max {
+                    return false;
+                }
+                match card_type {
+                    Some(t) => picks.iter().all(|i| hand[*i].get_card_types().contains(t)),
+                    None => true
+                }
+            }
+            (DecisionRequest::ChooseCards { cards, min, max, .. }, Decision::Cards(picks)) => {
+                DecisionRequest::distinct_in_range(picks, cards.len()) && picks.len() >= *min && picks.len() <= *max
+            }
+            (DecisionRequest::ChooseSupplyPile { choices, optional, .. }, Decision::SupplyPile(pick)) => {
+                match pick {
+                    Some(i) => *i < choices.len(),
+                    None => *optional || choices.is_empty()
+                }
+            }
+            (DecisionRequest::YesNo { .. }, Decision::YesNo(_)) => true,
+            (DecisionRequest::OrderCards { cards, .. }, Decision::Order(order)) => {
+                DecisionRequest::distinct_in_range(order, cards.len()) && order.len() == cards.len()
+            }
+            _ => false
+        }
+    }
+
+    /**
+     * default_decision
+     * The simplest valid answer: the fewest cards allowed, the first pile, no, or the order the cards came in.
+     * Used when an agent gives an answer that doesn't fit the request.
+     */
+    pub fn default_decision(&self, hand: &[CardId]) -> Decision {
+        match self {
+            DecisionRequest::ChooseCardsFromHand { min, card_type, .. } => {
+                let eligible = (0..hand.len()).filter(|i| match card_type {
+                    Some(t) => hand[*i].get_card_types().contains(t),
+                    None => true
+                });
+                Decision::Cards(eligible.take(*min).collect())
+            }
+            DecisionRequest::ChooseCards { min, .. } => Decision::Cards((0..*min).collect()),
+            DecisionRequest::ChooseSupplyPile { choices, optional, .. } => {
+                if *optional || choices.is_empty() {
+                    Decision::SupplyPile(None)
+                }
+                else {
+                    Decision::SupplyPile(Some(0))
+                }
+            }
+            DecisionRequest::YesNo { .. } => Decision::YesNo(false),
+            DecisionRequest::OrderCards { cards, .. } => Decision::Order((0..cards.len()).collect())
+        }
+    }
+
+    /**
+     * valid_decisions
+     * Every acceptable answer to this request, given the deciding player's hand.
+     * Picks of cards come in index order, as picking the same cards in another order is the same answer.
+     * There can be a lot of these for a big hand (every way to discard from it, for a Cellar), so only ask when you need them all.
+     */
+    pub fn valid_decisions(&self, hand: &[CardId]) -> Vec<Decision> {
+        match self {
+            DecisionRequest::ChooseCardsFromHand { min, max, card_type, .. } => {
+                let eligible: Vec<usize> = (0..hand.len()).filter(|i| match card_type {
+                    Some(t) => hand[*i].get_card_types().contains(t),
+                    None => true
+                }).collect();
+                DecisionRequest::subsets(&eligible, *min, *max).into_iter().map(Decision::Cards).collect()
+            }
+            DecisionRequest::ChooseCards { cards, min, max, .. } => {
+                let indices: Vec<usize> = (0..cards.len()).collect();
+                DecisionRequest::subsets(&indices, *min, *max).into_iter().map(Decision::Cards).collect()
+            }
+            DecisionRequest::ChooseSupplyPile { choices, optional, .. } => {
+                let mut decisions: Vec<Decision> = (0..choices.len()).map(|i| Decision::SupplyPile(Some(i))).collect();
+                if *optional || choices.is_empty() {
+                    decisions.push(Decision::SupplyPile(None));
+                }
+                decisions
+            }
+            DecisionRequest::YesNo { .. } => vec![Decision::YesNo(true), Decision::YesNo(false)],
+            DecisionRequest::OrderCards { cards, .. } => {
+                let mut orders = Vec::new();
+                DecisionRequest::permutations(&mut Vec::new(), cards.len(), &mut orders);
+                orders.into_iter().map(Decision::Order).collect()
+            }
+        }
+    }
+
+    /**
+     * Helper: every way to pick between min and max of the given indices, keeping them in the order given.
+     */
+    fn subsets(indices: &[usize], min: usize, max: usize) -> Vec<Vec<usize>> {
+        let mut picks = vec![Vec::new()];
+        for i in indices {
+            // Every pick so far, with and without this index (as long as that isn't too many).
+            let mut with_this: Vec<Vec<usize>> = picks.iter().filter(|p| p.len() < max).map(|p| {
+                let mut p = p.clone();
+                p.push(*i);
+                p
+            }).collect();
+            picks.append(&mut with_this);
+        }
+        picks.retain(|p| p.len() >= min);
+        picks.sort();
+        return picks;
+    }
+
+    /**
+     * Helper: every order of the indices 0 to count, each one built onto the order so far.
+     */
+    fn permutations(order: &mut Vec<usize>, count: usize, orders: &mut Vec<Vec<usize>>) {
+        if order.len() == count {
+            orders.push(order.clone());
+            return;
+        }
+        for i in 0..count {
+            if !order.contains(&i) {
+                order.push(i);
+                DecisionRequest::permutations(order, count, orders);
+                order.pop();
+            }
+        }
+    }
+
+    /**
+     * Helper: are all of the indices below the limit, with none repeated?
+     */
+    fn distinct_in_range(picks: &[usize], limit: usize) -> bool {
+        for (n, i) in picks.iter().enumerate() {
+            if *i >= limit || picks[..n].contains(i) {
+                return false;
+            }
+        }
+        return true;
+    }
+}
+
+/**
+ * InteractiveAgent
+ * The agent for a person playing through a frontend (like the TUI).
+ * It always defers, so the frontend can show the request and answer it once the person has chosen.
+ */
+pub struct InteractiveAgent;
+
+impl Agent for InteractiveAgent {
+    fn decide(&mut self, _view: &DecisionView, _request: &DecisionRequest) -> Option<Decision> {
+        return None;
+    }
+}
+
+/**
+ * BasicBotAgent
+ * The built-in CPU's decision maker.
+ * Nothing clever: it gets rid of the worst cards it's asked to, gains the most expensive card it can,
+ * and always reveals a reaction when asked.
+ */
+pub struct BasicBotAgent;
+
+impl Agent for BasicBotAgent {
+    fn decide(&mut self, view: &DecisionView, request: &DecisionRequest) -> Option<Decision> {
+        match request {
+            DecisionRequest::ChooseCardsFromHand { min, card_type, .. } => {
+                // Pick the cheapest eligible cards first, as they're usually the ones we want to lose.
+                let mut eligible: Vec<usize> = (0..view.hand.len()).filter(|i| match card_type {
+                    Some(t) => view.hand[*i].get_card_types().contains(t),
+                    None => true
+                }).collect();
+                eligible.sort_by_key(|i| view.hand[*i].get_cost());
+                eligible.truncate(*min);
+                Some(Decision::Cards(eligible))
+            }
+            DecisionRequest::ChooseCards { min, .. } => Some(Decision::Cards((0..*min).collect())),
+            DecisionRequest::ChooseSupplyPile { choices, .. } => {
+                // Take the most expensive card on offer.
+                let mut best: Option<usize> = None;
+                for (i, choice) in choices.iter().enumerate() {
+                    match best {
+                        Some(b) if choices[b].cost >= choice.cost => {}
+                        _ => best = Some(i)
+                    }
+                }
+                Some(Decision::SupplyPile(best))
+            }
+            DecisionRequest::YesNo { .. } => Some(Decision::YesNo(true)),
+            DecisionRequest::OrderCards { cards, .. } => Some(Decision::Order((0..cards.len()).collect()))
+        }
+    }
+}
+
+/**
+ * ScriptedAgent
+ * Answers decisions from a list given up front, in order. Meant for tests and scripted games.
+ * Once the script runs out, it defers like an interactive player.
+ */
+pub struct ScriptedAgent {
+    answers: VecDeque<Decision>
+}
+
+impl ScriptedAgent {
+    pub fn new(answers: Vec<Decision>) -> ScriptedAgent {
+        ScriptedAgent {
+            answers: VecDeque::from(answers)
+        }
+    }
+}
+
+impl Agent for ScriptedAgent {
+    fn decide(&mut self, _view: &DecisionView, _request: &DecisionRequest) -> Option<Decision> {
+        return self.answers.pop_front();
+    }
+}
@@ -5,19 +5,25 @@ phases.rs
 Defines the phases that a player goes through in each game.
 */
 
+use serde::{Deserialize, Serialize};
+
 /**
  * The phases, or "states" that a player is in at any time.
  * Idle is for when the opponent is playing
+ * Start is the very start of a turn, before any actions.
  * Action is for when actions can be played
  * Buy is for treasures and buying cards.
+ * Night is for night cards, after buying. It's skipped if the player has none.
  * Cleanup is for discarding and drawing anew,
  * Never is a flag value for cards like "Victory" which will never be played and need a playerphase.
  */
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PlayerPhases {
     Idle = -1,
+    Start,
     Action,
     Buy,
+    Night,
     Cleanup,
     Never
 }
@@ -28,12 +34,18 @@ pub enum PlayerPhases {
 impl PlayerPhases {
     pub fn to_string(&self) -> String {
         match self {
+            PlayerPhases::Start => {
+                return String::from("Start of turn");
+            }
             PlayerPhases::Action => {
                 return String::from("Actions");
             }
             PlayerPhases::Buy => {
                 return String::from("Buy");
             }
+            PlayerPhases::Night => {
+                return String::from("Night");
+            }
             PlayerPhases::Cleanup => {
                 return String::from("Cleanup");
             }
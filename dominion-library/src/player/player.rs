@@ -6,50 +6,87 @@ Player class.
 Defines the complicated relationships, data types, and references needed to run a player.
 */
 
+use serde::{Deserialize, Serialize};
+
 use crate::card_manager::card::Card;
-use crate::card_manager::card_properties::CardTypes;
+use crate::card_manager::card_properties::{ActionProperties, CardTypes};
+use crate::card_manager::card_registry::{card_registry, CardId};
 use crate::player::phases::PlayerPhases;
-use crate::game::board::{Board, PlayerInterface};
+use crate::player::seat::SeatKind;
+use crate::player::strategy::StrategyKind;
 use crate::game::game_errors::{GameErrors, InvalidActionError};
-use crate::game::player_middleware::PlayerMiddleware;
+use crate::game::game_event::{DrawnCards, GameEvent};
+use crate::game::card_effects::{self, TreasureTrigger};
 use crate::game::board::CardSet;
-use rand::thread_rng;
+use crate::game::game_command::GameCommand;
+use crate::game::game_manager::GameManager;
 use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
 use std::collections::VecDeque;
-use std::{
-    cell::RefCell, rc::Rc
-};
 
 
+/**
+ * GainDestination
+ * Where a gained card ends up.
+ * Bought cards always go to the discard, but some cards gain straight into the hand or onto the deck.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum GainDestination {
+    Discard,
+    Hand,
+    DeckTop
+}
+
 /**
  * Player struct
  * Structure containing all player data.
+ * A player is plain data: it doesn't reach out to the board or the log itself.
+ * Anything that touches those (buying, gaining, trashing) goes through the GameState that owns the player.
  */
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
-    // Name of the player, currently unused.
+    // Name of the player, shown in the log and the final scores.
     pub(crate) name: String,
 
+    // Whether a human or a bot is playing this seat, and how the bot plays.
+    pub(crate) kind: SeatKind,
+    // Saves from before strategies were added had Big Money bots, so that's what they get.
+    #[serde(default)]
+    pub(crate) strategy: StrategyKind,
+
+    // Which seat this player is in, so the events below can say who they happened to.
+    pub(crate) seat: usize,
+    // Things that happened to this player's cards (plays, shuffles and draws) that the game hasn't collected yet.
+    // They're always collected before the game could be saved, so they aren't.
+    #[serde(skip)]
+    pub(crate) events: Vec<GameEvent>,
+
     // The players deck (where they draw from)
-    pub(crate) deck: VecDeque<Box<dyn Card>>,
+    pub(crate) deck: VecDeque<CardId>,
     // The player's discard (Where cards go after they get used)
-    pub(crate) discard: VecDeque<Box<dyn Card>>,
+    pub(crate) discard: VecDeque<CardId>,
     // The player's hand (What they can play)
-    pub(crate) hand: Vec<Box<dyn Card>>,
+    pub(crate) hand: Vec<CardId>,
     // The cards of the player that are in play.
-    pub(crate) in_play: Vec<Box<dyn Card>>,
+    pub(crate) in_play: Vec<CardId>,
     // What phase the player is in.
     pub(crate) phase: PlayerPhases,
 
-    // A refCell to the board. used for purchasing cards.
-    pub(crate) board: Rc<RefCell<Board>>,
-
-    // Player Middleware refCell.
-    pub(crate) player_middleware: Rc<RefCell<PlayerMiddleware>>,
+    // This player's random number generator, seeded from the game's seed (on a stream of its own).
+    // Every shuffle pulls from this so a game can be replayed from its seed.
+    pub(crate) rng: ChaCha8Rng,
 
     // Implement defaults for these two, and all parameters of Player.
     pub (crate) buy_power: u8,
     pub (crate) actions_remaining: u8,
-    pub (crate) buys: u8
+    pub (crate) buys: u8,
+
+    // How many turns this player has finished. Used to break ties at the end of the game.
+    pub (crate) turns_taken: u32,
+
+    // Effects waiting on this player to play a treasure this turn (like Merchant's), and how many silvers they've played.
+    pub (crate) treasure_triggers: Vec<TreasureTrigger>,
+    pub (crate) silvers_played: u8
 
 }
 
@@ -62,31 +99,36 @@ impl Player {
      * Creates a new player object,
      * with most properties empty so they can be set.
      */
-    pub(crate) fn new(deck: VecDeque<Box<dyn Card>>, board_ref: Rc<RefCell<Board>>, middle: Rc<RefCell<PlayerMiddleware>>) -> Player {
+    pub(crate) fn new(seat: usize, deck: VecDeque<CardId>, rng: ChaCha8Rng) -> Player {
         return Player {
             name: String::from("No name supplied."),
+            kind: SeatKind::Human,
+            strategy: StrategyKind::default(),
+            seat: seat,
+            events: Vec::new(),
             deck: deck,
-            discard: VecDeque::<Box<dyn Card>>::new(),
-            hand: Vec::<Box<dyn Card>>::new(),
-            in_play: Vec::<Box<dyn Card>>::new(),
+            discard: VecDeque::<CardId>::new(),
+            hand: Vec::<CardId>::new(),
+            in_play: Vec::<CardId>::new(),
             phase: PlayerPhases::Idle,
-            board: board_ref,
-            player_middleware: middle,
+            rng: rng,
 
 
             buy_power: 0,
             actions_remaining: 1,
-            buys: 1
+            buys: 1,
+            turns_taken: 0,
+            treasure_triggers: Vec::new(),
+            silvers_played: 0
         };
     }
 
     /**
      * cleanup_and_draw
      * Cleanup the player's playing area, and draw five new cards from the deck.
+     * The player's phase is left alone: the TurnController owns it.
      */
     pub(crate) fn cleanup_and_draw(&mut self) {
-        self.phase = PlayerPhases::Idle;
-
         // Push all cards from the player's hand to the discard.
         while self.hand.len() > 0 {
             self.discard.push_front(self.hand.pop().expect("No more cards in hand to discard"));
@@ -98,15 +140,14 @@ impl Player {
         }
 
         // Pick up five cards.
-        for _ in 0..5 {
-            self.draw_card();
-        }
+        self.draw_cards(5);
 
         // Reset player properties to pre-playing for next turn.
         self.buy_power = 0;
         self.actions_remaining = 1;
-        self.phase = PlayerPhases::Idle;
         self.buys = 1;
+        self.treasure_triggers.clear();
+        self.silvers_played = 0;
     }
 
     /**
@@ -118,36 +159,24 @@ impl Player {
         let card = self.hand.get(hand_index).unwrap();
 
         if card.get_playing_phase() == &self.phase {
+            // Check for an action before taking the card out of the hand, so a refused play leaves the hand as it was.
+            let is_action = !card.get_card_types().contains(&CardTypes::Treasure) && card.get_card_types().contains(&CardTypes::Action);
+            if is_action && self.actions_remaining < 1 {
+                return Some(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("Attempted to play card without actions.") }))
+            }
 
             let card = self.hand.remove(hand_index);
             if card.get_card_types().contains(&CardTypes::Treasure) {
                 self.buy_power += card.get_relevant_value() as u8;
+                card_effects::fire_treasure_triggers(self, card);
 
-            } else if card.get_card_types().contains(&CardTypes::Action) {
-                if self.actions_remaining >= 1 {
-                    let props = card.get_action_properties();
-
-                    self.actions_remaining += props.added_actions;
-
-                    self.buys += props.added_buys;
-
-                    self.buy_power += props.temp_coin;
-
-                    for _ in 0.. props.added_cards {
-                        self.draw_card();
-                    }
-                    self.actions_remaining -= 1;
-
-                }
-                else {
-                    self.hand.push(card);
-                    return Some(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("Attempted to play card without actions.") }))
-                }
+            } else if is_action {
+                self.apply_action_properties(&card.get_action_properties());
+                self.actions_remaining -= 1;
 
             }
             self.in_play.push(card);
-            //self.player_middleware.borrow_mut().played_card_middleware(card);
-
+            self.events.push(GameEvent::CardPlayed { seat: self.seat, card: card });
 
             None
         }
@@ -157,48 +186,31 @@ impl Player {
     }
 
     /**
-     * buy_card
-     * Buy a card from the board given an index and a cardset.
-     * Deducts from buys and buy power as needed.
-     * Returns an error if there is one.
+     * apply_action_properties
+     * Give the player an action card's +Actions, +Buys, +Coins and +Cards.
+     * Doesn't use up an action, as cards played by other cards (like Throne Room) don't.
      */
-    pub (crate) fn buy_card(&mut self, pile_index: usize, c:CardSet) -> Option<GameErrors> {
-        let price = self.board.borrow_mut().get_card_price(pile_index, c.clone());
+    pub(crate) fn apply_action_properties(&mut self, props: &ActionProperties) {
+        self.actions_remaining += props.added_actions;
 
-        match price {
-            Ok(x) => {
-                if (self.buy_power as i32) < x || self.buys == 0 {
-                    return Some(GameErrors::InvalidActionError(InvalidActionError {action_attempted: String::from("You are out of buys, or out of money. Wait until next turn!")}))
-                }
-            }
-            Err(error) => {
-                return Some(error);
-            }
-        }
+        self.buys += props.added_buys;
 
+        self.buy_power += props.temp_coin;
 
-        let result = self.board.borrow_mut().buy_card(pile_index, c.clone());
-        self.buys -= 1;
-        self.buy_power -= price.unwrap() as u8;
-
-        match result {
-            Ok(x) => {
-                self.player_middleware.borrow_mut().bought_card_middleware(self.name.clone(),x.get_name().clone());
-                self.discard.push_front(x);
-                return None;
-            }
-            Err(error) => {
-                return Some(error);
-            }
-        }
+        self.draw_cards(props.added_cards as usize);
     }
 
     /**
      * Shuffle the deck and prepend the discard (behind the deck)
      */
     fn shuffle_and_prepend_discard(&mut self) {
-        // Make the discard contiguous so it can be shuffled using thread_rng.
-        self.discard.make_contiguous().shuffle(&mut thread_rng());
+        if self.discard.is_empty() {
+            return;
+        }
+
+        // Make the discard contiguous so it can be shuffled using the game's rng.
+        self.discard.make_contiguous().shuffle(&mut self.rng);
+        self.events.push(GameEvent::Shuffled { seat: self.seat });
 
         // While there's still cards in the discard, push them into the deck at the back.
         while self.discard.len() > 0 {
@@ -207,32 +219,38 @@ impl Player {
     }
 
     /**
-     * draw_card
-     * Draw a card from the deck.
+     * take_from_deck_top
+     * Take the top card off of the deck, shuffling the discard in first if the deck is empty.
+     * Returns nothing if there's no cards left in either.
      */
-    pub(crate) fn draw_card(&mut self) {
-        // Get a card from the deck.
-        let option_card = self.deck.pop_front();
-
-        // match the card, so if it doesn't exist, we can shuffle the discard.
-        match option_card {
-            Some(card) => {
-                // Push the card into the hand.
-                self.hand.push(card);
-            }
-            None => {
-                self.shuffle_and_prepend_discard();
-                // If there's no cards, shuffle the deck, then try to pick up a card.
-                if self.deck.len() != 0 {
-                    // Get the card, push it into the hand.
-                    let guarentee_card = self.deck.pop_front().expect("This should never happen: Added a card after making sure cards were available.");
-                    self.hand.push(guarentee_card);
-                    
-                }
-                // If there's no more cards in the deck after shuffling the discard, don't draw anything.
+    pub(crate) fn take_from_deck_top(&mut self) -> Option<CardId> {
+        // If there's no cards, shuffle the discard into the deck first.
+        if self.deck.is_empty() {
+            self.shuffle_and_prepend_discard();
+        }
+
+        // If there's no more cards in the deck after shuffling the discard, this is None.
+        return self.deck.pop_front();
+    }
+
+    /**
+     * draw_cards
+     * Draw cards from the deck into the hand, recording what was drawn.
+     * If there's nothing left to draw, the player draws as many as there are.
+     */
+    pub(crate) fn draw_cards(&mut self, count: usize) {
+        let mut drawn = Vec::new();
+        for _ in 0..count {
+            match self.take_from_deck_top() {
+                Some(card) => drawn.push(card),
+                None => break
             }
         }
-        
+
+        if !drawn.is_empty() {
+            self.hand.extend(drawn.iter());
+            self.events.push(GameEvent::Drew { seat: self.seat, cards: DrawnCards::Visible(drawn) });
+        }
     }
 
     /**
@@ -275,60 +293,27 @@ impl Player {
     pub(crate) fn shuffle_deck(&mut self) {
         // VecDeques are structs that efficiently insert at both the front and back of the vector.
         // This makes it helpful for decks in dominion, as you are required to insert at the front and back for multiple cards.
-        // However, the side effect is that they need to be made into normal, contiguous vectors to be shuffled using the thread_rng.
-        self.deck.make_contiguous().shuffle(&mut thread_rng());
-    }
-
-    /**
-     * Advance_phase
-     * Used by the player to skip through a phase.
-     */
-    pub(crate) fn advance_phase(&mut self) {
-        match self.phase {
-            PlayerPhases::Idle => {
-                self.phase = PlayerPhases::Action;
-            }
-            PlayerPhases::Action => {
-                self.phase = PlayerPhases::Buy;
-            }
-            PlayerPhases::Buy => {
-                self.phase = PlayerPhases::Cleanup;
-                self.cleanup_and_draw();
-            }
-            PlayerPhases::Cleanup => {
-                self.phase = PlayerPhases::Idle;
-            }
-            _ => {}
-        }
-    }
-
-    /**
-     * end_turn
-     * Ends your turn.
-     */
-    fn end_turn(&mut self) {
-        self.cleanup_and_draw();
+        // However, the side effect is that they need to be made into normal, contiguous vectors to be shuffled using the player's rng.
+        self.deck.make_contiguous().shuffle(&mut self.rng);
+        self.events.push(GameEvent::Shuffled { seat: self.seat });
     }
 
     /**
      * count_victory_points
      * Counts the number of victory points in your entire hand.
+     * Curses count too, taking points away, and Gardens are worth a point for every 10 cards the player has.
      * Functions as a sort of scoreboard.
      */
     fn count_victory_points(&self) -> i32 {
+        let card_count = (self.deck.len() + self.hand.len() + self.discard.len() + self.in_play.len()) as i32;
+
+        let gardens = card_registry().get_known_cards().gardens;
         let mut vp : i32 = 0;
-        for i in &self.deck {
-            if i.get_card_types().contains(&CardTypes::Victory) {
-                vp += i.get_relevant_value();
+        for i in self.deck.iter().chain(self.hand.iter()).chain(self.discard.iter()) {
+            if *i == gardens {
+                vp += card_count / 10;
             }
-        }
-        for i in &self.hand {
-            if i.get_card_types().contains(&CardTypes::Victory) {
-                vp += i.get_relevant_value();
-            }
-        }
-        for i in &self.discard {
-            if i.get_card_types().contains(&CardTypes::Victory) {
+            else if i.get_card_types().contains(&CardTypes::Victory) || i.get_card_types().contains(&CardTypes::Curse) {
                 vp += i.get_relevant_value();
             }
         }
@@ -337,18 +322,11 @@ impl Player {
 }
 
 /**
- * PlayerUIInterface
- * Wrapper methods handed to the UI to allow for access.
- * The effectiveness of this design choice is still in question.
+ * PlayerStatsInterface
+ * Read-only wrapper methods handed to the UI, to show a player's hand and how their turn is going.
  */
-pub trait PlayerUIInterface {
-    fn play_ui_card(&mut self, index: usize) -> Option<GameErrors>;
-
-    fn buy_ui_card(&mut self, index: usize, set:CardSet) -> Option<GameErrors>;
-
-    fn advance_ui_phase(&mut self);
-
-    fn get_hand(&self) -> &Vec<Box<dyn Card>>;
+pub trait PlayerStatsInterface {
+    fn get_hand(&self) -> &Vec<CardId>;
 
     fn get_actions(&self) -> u8;
 
@@ -358,75 +336,123 @@ pub trait PlayerUIInterface {
     
     fn get_buys(&self) -> u8;
 
-    fn end_turn(&mut self);
-
     fn get_phase(&self) -> PlayerPhases;
-    
-    fn play_all_treasures_ui(&mut self);
 }
 
-impl PlayerUIInterface for Player {
-    fn get_hand(&self) -> &Vec<Box<dyn Card>> {
+impl PlayerStatsInterface for Player {
+    fn get_hand(&self) -> &Vec<CardId> {
         return &self.hand;
     }
 
-    fn play_ui_card(&mut self, index: usize) -> Option<GameErrors> {
-        return self.play_card(index);
+    fn get_actions(&self) -> u8 {
+        return self.actions_remaining;
     }
 
-    fn buy_ui_card(&mut self, index: usize, set: CardSet) -> Option<GameErrors> {
-        self.buy_card(index, set)
+    fn get_buying_power(&self) -> u8 {
+        return self.buy_power;
     }
 
-    fn advance_ui_phase(&mut self) {
-        if self.phase == PlayerPhases::Action || self.phase == PlayerPhases::Buy {
-            self.advance_phase();
-        }
+    fn get_victory_points(&self) -> i32 {
+        return self.count_victory_points();
     }
 
-    fn end_turn(&mut self) {
-        if self.phase == PlayerPhases::Action || self.phase == PlayerPhases::Buy {
-            self.end_turn();
-        }
+    fn get_phase(&self) -> PlayerPhases {
+        return self.phase.clone();
     }
+
+    fn get_buys(&self) -> u8 {
+        return self.buys;
+    }
+}
+
+/**
+ * PlayerUIInterface
+ * Wrapper methods handed to the UI to allow for access.
+ * Buying needs the supply as well as the player, so these are given on a PlayerSeat (a player, along with the game they're in).
+ * The effectiveness of this design choice is still in question.
+ */
+pub trait PlayerUIInterface: PlayerStatsInterface {
+    fn buy_ui_card(&mut self, index: usize, set:CardSet) -> Option<GameErrors>;
     
+    fn play_all_treasures_ui(&mut self);
+}
+
+/**
+ * PlayerSeat
+ * A seat at the table, borrowed from the game.
+ * Handed out by the GameManager, so the UI can act as a player without holding onto the game's parts itself.
+ * Its moves are GameCommands made through GameManager::apply, so they're checked and recorded like any other.
+ */
+pub struct PlayerSeat<'a> {
+    pub(crate) game: &'a mut GameManager,
+    pub(crate) seat: usize
+}
+
+impl PlayerSeat<'_> {
+    fn player(&self) -> &Player {
+        return &self.game.get_state().players[self.seat];
+    }
+}
+
+impl PlayerStatsInterface for PlayerSeat<'_> {
+    fn get_hand(&self) -> &Vec<CardId> {
+        return self.player().get_hand();
+    }
+
     fn get_actions(&self) -> u8 {
-        return self.actions_remaining;
+        return self.player().get_actions();
     }
 
     fn get_buying_power(&self) -> u8 {
-        return self.buy_power;
+        return self.player().get_buying_power();
     }
 
     fn get_victory_points(&self) -> i32 {
-        return self.count_victory_points();
+        return self.player().get_victory_points();
     }
 
     fn get_phase(&self) -> PlayerPhases {
-        return self.phase.clone();
-    }
-
-    fn play_all_treasures_ui(&mut self) {
-        return self.play_treasures();
+        return self.player().get_phase();
     }
 
     fn get_buys(&self) -> u8 {
-        return self.buys.clone();
+        return self.player().get_buys();
     }
 }
 
-// Below this is unused methods for action cards.
-// Players would recieve a "ResolveCardRequestTicket"
-// For each action, and would change parameters as needed.
+impl PlayerUIInterface for PlayerSeat<'_> {
+    fn buy_ui_card(&mut self, index: usize, set: CardSet) -> Option<GameErrors> {
+        return self.game.apply(self.seat, GameCommand::Buy { set: set, index: index }).err();
+    }
 
-struct PlayerResolveCardRequestTicket {
-    actions_gained: Option<u8>,
-    cards_gained: Option<u8>,
-    trash_cards: Option<u8>,
-    gain_card_options: Option<Vec<Box<dyn Card>>>,
-    discard_cards: Option<u8>
+    // Nothing happens if there are no treasures to play (or it isn't the seat's buy phase).
+    fn play_all_treasures_ui(&mut self) {
+        let _ = self.game.apply(self.seat, GameCommand::PlayAllTreasures);
+    }
 }
 
-struct TurnModifier {
-
-}
\ No newline at end of file
+/**
+ * Seat tests
+ * Moves made through a PlayerSeat, checked to be made (and recorded) the same as moves applied to the game.
+ */
+#[cfg(test)]
+mod seat_tests {
+    use super::*;
+    use crate::player::seat::Seat;
+
+    #[test]
+    fn moves_through_a_seat_are_recorded() {
+        let mut gm = GameManager::new(vec![Seat::human("Alice"), Seat::human("Bob")], Some(6)).unwrap();
+        gm.apply(0, GameCommand::EndPhase).unwrap();
+        let (index, set) = gm.get_state().board.find_pile(card_registry().get_known_cards().copper).unwrap();
+
+        let mut seat = gm.get_player_mut_character(0);
+        seat.play_all_treasures_ui();
+        assert!(seat.buy_ui_card(index, set).is_none());
+        assert_eq!(seat.get_buys(), 0);
+        assert!(seat.buy_ui_card(index, set).is_some());
+
+        let moves: Vec<GameCommand> = gm.get_replay().unwrap().moves.into_iter().map(|m| m.command).collect();
+        assert_eq!(moves, [GameCommand::EndPhase, GameCommand::PlayAllTreasures, GameCommand::Buy { set: set, index: index }]);
+    }
+}
@@ -0,0 +1,68 @@
+/*
+SPUStudnet
+12/15/2024
+seat.rs
+Defines the seats at the table, and who (or what) is sitting in them.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::player::strategy::StrategyKind;
+
+/**
+ * SeatKind
+ * Whether a seat is controlled by a person at the keyboard, or by the built-in CPU.
+ */
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SeatKind {
+    Human,
+    Bot
+}
+
+/**
+ * Seat
+ * One seat at the table: a display name, who is playing it, and (for bots) the strategy they play.
+ * A list of these is handed to the GameManager to set up a game, in turn order.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Seat {
+    pub name: String,
+    pub kind: SeatKind,
+    // Replays from before strategies were added had Big Money bots, so that's what they get.
+    #[serde(default)]
+    pub strategy: StrategyKind
+}
+
+/**
+ * Seat implementation
+ */
+impl Seat {
+    /**
+     * Create a seat played by a human.
+     */
+    pub fn human(name: &str) -> Seat {
+        Seat {
+            name: String::from(name),
+            kind: SeatKind::Human,
+            strategy: StrategyKind::default()
+        }
+    }
+
+    /**
+     * Create a seat played by the CPU, playing Big Money.
+     */
+    pub fn bot(name: &str) -> Seat {
+        return Seat::bot_playing(name, StrategyKind::BigMoney);
+    }
+
+    /**
+     * Create a seat played by the CPU, playing the given strategy.
+     */
+    pub fn bot_playing(name: &str, strategy: StrategyKind) -> Seat {
+        Seat {
+            name: String::from(name),
+            kind: SeatKind::Bot,
+            strategy: strategy
+        }
+    }
+}
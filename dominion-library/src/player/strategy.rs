@@ -0,0 +1,386 @@
+/*
+SPUStudnet
+12/15/2024
+strategy.rs
+Defines how bots play their turns: the Strategy trait, the view of the game it plays from,
+and the built-in strategies (Big Money, Big Money Ultimate, Smithy Big Money and a simple engine builder).
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::card_manager::card::Card;
+use crate::card_manager::card_properties::CardTypes;
+use crate::card_manager::card_registry::{card_registry, CardId};
+use crate::card_manager::card_structures::Pile;
+use crate::game::board::Board;
+use crate::game::game_state::GameState;
+
+/**
+ * StrategyView
+ * What a strategy gets to look at when choosing what to do: its own seat's cards and turn, and the supply.
+ * It can't change anything, and it can't see anyone else's hand or deck.
+ */
+pub struct StrategyView<'a> {
+    pub seat: usize,
+    state: &'a GameState
+}
+
+/**
+ * StrategyView implementation
+ */
+impl<'a> StrategyView<'a> {
+    /**
+     * Create a view of the game for the strategy playing the given seat.
+     */
+    pub(crate) fn new(seat: usize, state: &'a GameState) -> StrategyView<'a> {
+        return StrategyView { seat: seat, state: state };
+    }
+
+    // The cards in our hand.
+    pub fn get_hand(&self) -> &Vec<CardId> {
+        return &self.state.players[self.seat].hand;
+    }
+
+    // How many actions, buys and coins we have left this turn.
+    pub fn get_actions(&self) -> u8 {
+        return self.state.players[self.seat].actions_remaining;
+    }
+
+    pub fn get_buys(&self) -> u8 {
+        return self.state.players[self.seat].buys;
+    }
+
+    pub fn get_buying_power(&self) -> u8 {
+        return self.state.players[self.seat].buy_power;
+    }
+
+    // How many turns we've finished.
+    pub fn get_turns_taken(&self) -> u32 {
+        return self.state.players[self.seat].turns_taken;
+    }
+
+    // Every card we own, wherever it is (deck, hand, discard or in play).
+    pub fn get_owned_cards(&self) -> impl Iterator<Item = &CardId> {
+        let player = &self.state.players[self.seat];
+        return player.deck.iter().chain(player.hand.iter()).chain(player.discard.iter()).chain(player.in_play.iter());
+    }
+
+    // How many cards we own in total.
+    pub fn count_cards(&self) -> usize {
+        return self.get_owned_cards().count();
+    }
+
+    // How many copies of a card we own.
+    pub fn count_owned(&self, card: CardId) -> usize {
+        return self.get_owned_cards().filter(|c| **c == card).count();
+    }
+
+    // How many action cards we own, and how many of those give at least one more action (villages and cantrips).
+    pub fn count_actions(&self) -> usize {
+        return self.get_owned_cards().filter(|c| c.get_card_types().contains(&CardTypes::Action)).count();
+    }
+
+    pub fn count_non_terminals(&self) -> usize {
+        return self.get_owned_cards().filter(|c| is_non_terminal(c)).count();
+    }
+
+    // The coins every treasure we own adds up to.
+    pub fn get_total_money(&self) -> i32 {
+        return self.get_owned_cards().filter(|c| c.get_card_types().contains(&CardTypes::Treasure)).map(|c| c.get_relevant_value()).sum();
+    }
+
+    // How many of a card are left in the supply (None if it isn't in this game).
+    pub fn get_pile_count(&self, card: CardId) -> Option<u8> {
+        return self.piles().find(|p| p.card == card).map(|p| p.cards_left);
+    }
+
+    // What a card costs (None if it isn't in this game).
+    pub fn get_pile_cost(&self, card: CardId) -> Option<i32> {
+        return self.piles().find(|p| p.card == card).map(|p| p.get_card_price());
+    }
+
+    // The kingdom cards in this game that still have cards left.
+    pub fn get_kingdom_cards(&self) -> Vec<CardId> {
+        return self.state.board.kingdom_set.iter().filter(|p| !p.is_empty()).map(|p| p.card).collect();
+    }
+
+    // The fewest cards that could be gained to end the game: the provinces left,
+    // or the cards left in the smallest piles that would have to run out.
+    pub fn get_gains_to_end(&self) -> usize {
+        let provinces = self.get_pile_count(card_registry().get_known_cards().province).unwrap_or(0) as usize;
+        let mut counts: Vec<usize> = self.piles().map(|p| p.cards_left as usize).collect();
+        counts.sort();
+        let piles = counts.iter().take(Board::empty_piles_to_end(self.state.players.len())).sum();
+        return provinces.min(piles);
+    }
+
+    /**
+     * Helper: every supply pile.
+     */
+    fn piles(&self) -> impl Iterator<Item = &Pile> {
+        let board = &self.state.board;
+        return board.victory_cards.iter().chain(board.treasure_cards.iter()).chain(board.kingdom_set.iter());
+    }
+}
+
+/**
+ * Strategy trait
+ * How a bot plays its turns. The game asks it which action to play, over and over, until it says to stop
+ * (or runs out of actions), then plays every treasure and asks it what to buy until it says to stop (or runs out of buys).
+ * Choices the game can't make (an action it can't play, a card it can't afford) end that part of the turn.
+ * Decisions its cards ask for are still answered by the seat's Agent.
+ * Strategies have to be Send and Sync, as the game holding them can be moved to other threads.
+ */
+pub trait Strategy: Send + Sync {
+    // The index in hand of the action to play next, or None to stop playing actions.
+    fn choose_action(&mut self, view: &StrategyView) -> Option<usize>;
+    // The card to buy next, or None to stop buying.
+    fn choose_buy(&mut self, view: &StrategyView) -> Option<CardId>;
+}
+
+/**
+ * StrategyKind
+ * The built-in strategies, picked for a seat when the game is set up (see Seat::bot_playing).
+ * Saved with the game, so a loaded (or forked) game's bots carry on playing the same way.
+ */
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum StrategyKind {
+    // Buys Province, Gold or Silver with whatever it has, and never plays actions. The simplest strategy with a good chance of winning.
+    #[default]
+    BigMoney,
+    // Big Money that waits for enough money before buying Provinces, and greens (Duchies, then Estates) as the game ends.
+    BigMoneyUltimate,
+    // Big Money Ultimate with a Smithy or two to play.
+    SmithyBigMoney,
+    // Builds up villages and draw from the kingdom, plays them in the right order, then buys Provinces.
+    EngineBuilder
+}
+
+/**
+ * StrategyKind implementation
+ */
+impl StrategyKind {
+    /**
+     * build
+     * A new strategy of this kind, for a seat to play with.
+     */
+    pub fn build(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::BigMoney => Box::new(BigMoney),
+            StrategyKind::BigMoneyUltimate => Box::new(BigMoneyUltimate),
+            StrategyKind::SmithyBigMoney => Box::new(SmithyBigMoney { smithy: CardId::named("Smithy") }),
+            StrategyKind::EngineBuilder => Box::new(EngineBuilder)
+        }
+    }
+}
+
+/**
+ * BigMoney
+ * Province at 8, Gold at 6, Silver at 3. Nothing else.
+ */
+pub struct BigMoney;
+
+impl Strategy for BigMoney {
+    fn choose_action(&mut self, _view: &StrategyView) -> Option<usize> {
+        return None;
+    }
+
+    fn choose_buy(&mut self, view: &StrategyView) -> Option<CardId> {
+        let known = card_registry().get_known_cards();
+        return first_affordable(view, &[known.province, known.gold, known.silver]);
+    }
+}
+
+/**
+ * BigMoneyUltimate
+ * Gold until there's enough money in the deck to keep buying Provinces,
+ * then Duchies and Estates once the game is close to ending.
+ */
+pub struct BigMoneyUltimate;
+
+impl Strategy for BigMoneyUltimate {
+    fn choose_action(&mut self, _view: &StrategyView) -> Option<usize> {
+        return None;
+    }
+
+    fn choose_buy(&mut self, view: &StrategyView) -> Option<CardId> {
+        let known = card_registry().get_known_cards();
+        let gains_to_end = view.get_gains_to_end();
+        let mut wants = Vec::new();
+        if view.get_total_money() > 18 || gains_to_end <= 6 {
+            wants.push(known.province);
+        }
+        if gains_to_end <= 5 {
+            wants.push(known.duchy);
+        }
+        if gains_to_end <= 2 {
+            wants.push(known.estate);
+        }
+        wants.extend([known.gold, known.silver]);
+        return first_affordable(view, &wants);
+    }
+}
+
+/**
+ * SmithyBigMoney
+ * Big Money Ultimate with one Smithy, and a second once the deck has grown to 16 cards. Plays Smithy whenever it can.
+ * Without Smithy in the kingdom, it's just Big Money Ultimate.
+ */
+pub struct SmithyBigMoney {
+    // Smithy's id, looked up when the strategy is built (None if the loaded cards don't have one).
+    smithy: Option<CardId>
+}
+
+impl Strategy for SmithyBigMoney {
+    fn choose_action(&mut self, view: &StrategyView) -> Option<usize> {
+        let smithy = self.smithy?;
+        return view.get_hand().iter().position(|c| *c == smithy);
+    }
+
+    fn choose_buy(&mut self, view: &StrategyView) -> Option<CardId> {
+        let known = card_registry().get_known_cards();
+        let gains_to_end = view.get_gains_to_end();
+        let mut wants = Vec::new();
+        if view.get_total_money() > 15 {
+            wants.push(known.province);
+        }
+        if gains_to_end <= 4 {
+            wants.push(known.duchy);
+        }
+        if gains_to_end <= 2 {
+            wants.push(known.estate);
+        }
+        wants.push(known.gold);
+        if gains_to_end <= 6 {
+            wants.push(known.duchy);
+        }
+        if let Some(smithy) = self.smithy {
+            let smithies = view.count_owned(smithy);
+            if smithies < 1 || (smithies < 2 && view.count_cards() >= 16) {
+                wants.push(smithy);
+            }
+        }
+        wants.push(known.silver);
+        return first_affordable(view, &wants);
+    }
+}
+
+/**
+ * EngineBuilder
+ * Buys the best action it can afford from the kingdom (villages while it has more terminals than villages, draw otherwise)
+ * until about a third of its deck is actions, then money and Provinces.
+ * Plays villages and cantrips first, then the terminal that draws the most.
+ */
+pub struct EngineBuilder;
+
+impl Strategy for EngineBuilder {
+    fn choose_action(&mut self, view: &StrategyView) -> Option<usize> {
+        let hand = view.get_hand();
+        let actions = (0..hand.len()).filter(|i| hand[*i].get_card_types().contains(&CardTypes::Action));
+
+        // Non-terminals first (the most actions, then the most expensive), as they leave actions for the rest.
+        // Then the terminal that draws the most, and after that, the most expensive.
+        return actions.max_by_key(|i| {
+            let props = hand[*i].get_action_properties();
+            (is_non_terminal(&hand[*i]), props.added_actions, props.added_cards, hand[*i].get_cost())
+        });
+    }
+
+    fn choose_buy(&mut self, view: &StrategyView) -> Option<CardId> {
+        let known = card_registry().get_known_cards();
+        let gains_to_end = view.get_gains_to_end();
+        let mut wants = Vec::new();
+        if view.get_total_money() > 15 || gains_to_end <= 6 {
+            wants.push(known.province);
+        }
+        if gains_to_end <= 4 {
+            wants.push(known.duchy);
+        }
+        if gains_to_end <= 2 {
+            wants.push(known.estate);
+        }
+
+        // Keep building while under a third of the deck is actions.
+        if view.count_actions() * 3 < view.count_cards() {
+            let need_villages = view.count_actions() - view.count_non_terminals() > view.count_non_terminals();
+            let mut kingdom: Vec<CardId> = view.get_kingdom_cards().into_iter()
+                .filter(|c| c.get_card_types().contains(&CardTypes::Action) && c.get_cost() >= 3)
+                .collect();
+            // Best first: what's needed (villages or draw), then the most expensive.
+            kingdom.sort_by_key(|c| {
+                let props = c.get_action_properties();
+                let fits = if need_villages { is_non_terminal(c) } else { props.added_cards > 0 };
+                std::cmp::Reverse((fits, c.get_cost()))
+            });
+            wants.extend(kingdom);
+        }
+
+        wants.extend([known.gold, known.silver]);
+        return first_affordable(view, &wants);
+    }
+}
+
+/**
+ * Helper: whether an action gives back at least the action it used (a village or a cantrip).
+ */
+fn is_non_terminal(card: &CardId) -> bool {
+    return card.get_card_types().contains(&CardTypes::Action) && card.get_action_properties().added_actions > 0;
+}
+
+/**
+ * Helper: the first card in the list that's in the game, has cards left, and can be afforded right now.
+ */
+fn first_affordable(view: &StrategyView, wants: &[CardId]) -> Option<CardId> {
+    for want in wants {
+        match (view.get_pile_count(*want), view.get_pile_cost(*want)) {
+            (Some(left), Some(cost)) if left > 0 && cost <= view.get_buying_power() as i32 => return Some(*want),
+            _ => {}
+        }
+    }
+    return None;
+}
+
+/**
+ * Strategy tests
+ * Seeded games between bots, played to the end.
+ */
+#[cfg(test)]
+mod strategy_tests {
+    use super::*;
+    use crate::game::game_manager::GameManager;
+    use crate::game::kingdom::KingdomChoice;
+    use crate::player::seat::Seat;
+
+    // Play a seeded game between the given strategies on the First Game kingdom, returning the finished game.
+    fn play(strategies: &[StrategyKind], seed: u64) -> GameManager {
+        let seats = strategies.iter().enumerate().map(|(i, s)| Seat::bot_playing(&format!("Bot {}", i), *s)).collect();
+        return GameManager::simulate(seats, Some(seed), KingdomChoice::Preset(String::from("First Game"))).unwrap();
+    }
+
+    // Every card the seat owns, by name.
+    fn owned(gm: &GameManager, seat: usize) -> Vec<String> {
+        let p = &gm.get_state().players[seat];
+        return p.hand.iter().chain(p.deck.iter()).chain(p.discard.iter()).chain(p.in_play.iter()).map(|c| c.get_name().clone()).collect();
+    }
+
+    #[test]
+    fn big_money_finishes_a_seeded_game() {
+        let gm = play(&[StrategyKind::BigMoney, StrategyKind::BigMoney], 13);
+        let result = gm.check_ending().expect("The bots should have played the game to the end.");
+
+        assert!(!result.get_winners().is_empty());
+        assert!(result.standings.iter().all(|s| s.victory_points > 0));
+        for seat in 0..2 {
+            let only_money_and_victories = owned(&gm, seat).iter().all(|c| ["Copper", "Silver", "Gold", "Estate", "Province"].contains(&c.as_str()));
+            assert!(only_money_and_victories, "Big Money only buys Silver, Gold and Provinces.");
+        }
+    }
+
+    #[test]
+    fn the_same_seed_plays_out_the_same_game() {
+        let scores = |gm: &GameManager| gm.check_ending().unwrap().standings.iter().map(|s| (s.seat, s.victory_points, s.turns_taken)).collect::<Vec<_>>();
+        let strategies = [StrategyKind::BigMoney, StrategyKind::SmithyBigMoney, StrategyKind::BigMoneyUltimate, StrategyKind::EngineBuilder];
+
+        assert_eq!(scores(&play(&strategies, 13)), scores(&play(&strategies, 13)));
+    }
+}
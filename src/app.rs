@@ -5,10 +5,17 @@ card_manager.rs
 Reference module, required by rust to reference modules in folder structures.
 */
 
+use dominion_library::card_manager::card::Card;
+use dominion_library::game::board::CardSet;
+use dominion_library::game::game_command::GameCommand;
 use dominion_library::game::game_errors::GameErrors;
-use dominion_library::player::player::PlayerUIInterface;
+use dominion_library::game::game_event::GameEvent;
+use dominion_library::player::player::PlayerStatsInterface;
 use dominion_library::game::game_manager::GameManager as gm;
+use dominion_library::game::game_results::GameResult;
 use dominion_library::player::phases::PlayerPhases;
+use dominion_library::player::seat::{Seat, SeatKind};
+use dominion_library::player::agent::{Decision, DecisionRequest};
 
 
 /**
@@ -34,7 +41,11 @@ pub struct App {
     pub prev_screen: Option<CurrentScreen>,
     pub update_items: UpdateItems,
     pub game: gm,
-    pub end_data: Option<Vec<(String, i32)>>
+    // The seat the person at the keyboard is playing.
+    pub player_seat: usize,
+    // The moves that seat can make right now. Worked out again each time the game changes, rather than every frame.
+    pub legal_moves: Vec<GameCommand>,
+    pub end_data: Option<GameResult>
 }
 
 
@@ -50,25 +61,101 @@ impl App {
      * Creates a new app with blank data on the welcome screen.
      */
     pub fn new() -> App {
+        let game = gm::new(vec![Seat::human("Player"), Seat::bot("CPU")], None).expect("The default table should always be a valid setup.");
+        let legal_moves = game.legal_moves(0);
         App {
             game_nav_data: GameNavData::new(),
             current_screen: CurrentScreen::Welcome,
             welcome_data: WelcomeScreen::new(),
             prev_screen: None,
             update_items: UpdateItems::new(),
-            game: gm::new(),
+            game: game,
+            player_seat: 0,
+            legal_moves: legal_moves,
             end_data: None
         }
         
     }
 
     /**
-     * toggle_menu_button
-     * Used to move between the two buttons on the main menu.
-     * Independent of what you press, you're moving to the next one.
+     * apply_move
+     * Make a move as our seat, then work out what we can do next.
      */
-    pub fn toggle_menu_button(&mut self) {
-        self.welcome_data.toggle_selected_button();
+    pub fn apply_move(&mut self, command: GameCommand) -> Result<Vec<GameEvent>, GameErrors> {
+        let result = self.game.apply(self.player_seat, command);
+        self.refresh_legal_moves();
+        return result;
+    }
+
+    /**
+     * refresh_legal_moves
+     * Work out the moves our seat can make, after the game has changed.
+     */
+    pub fn refresh_legal_moves(&mut self) {
+        self.legal_moves = self.game.legal_moves(self.player_seat);
+    }
+
+    /**
+     * next_menu_button / previous_menu_button
+     * Used to move between the buttons on the main menu, wrapping around at either end.
+     */
+    pub fn next_menu_button(&mut self) {
+        self.welcome_data.move_selected_button(1);
+    }
+
+    pub fn previous_menu_button(&mut self) {
+        self.welcome_data.move_selected_button(WELCOME_BUTTONS.len() - 1);
+    }
+
+    /**
+     * save_path
+     * Where the game is saved to and continued from.
+     * It's dominion_save.json in the folder the game is run from, unless DOMINION_SAVE points somewhere else.
+     */
+    pub fn save_path() -> String {
+        return std::env::var("DOMINION_SAVE").unwrap_or(String::from("dominion_save.json"));
+    }
+
+    /**
+     * save_game
+     * Save the game being played, and say how it went on the main menu.
+     */
+    pub fn save_game(&mut self) {
+        let path = App::save_path();
+        match self.game.save(&path) {
+            Ok(()) => self.welcome_data.message = Some(format!("Game saved to {}.", path)),
+            Err(GameErrors::SaveFileError(e)) => self.welcome_data.message = Some(e.to_string()),
+            Err(_) => self.welcome_data.message = Some(String::from("An unknown error occured while saving."))
+        }
+    }
+
+    /**
+     * continue_game
+     * Load the saved game, and carry on playing it from where it was saved.
+     * If it can't be loaded, stay on the main menu and say why.
+     */
+    pub fn continue_game(&mut self) {
+        let game = match gm::load(&App::save_path()) {
+            Ok(g) => g,
+            Err(GameErrors::SaveFileError(e)) => {
+                self.welcome_data.message = Some(e.to_string());
+                return;
+            }
+            Err(_) => {
+                self.welcome_data.message = Some(String::from("An unknown error occured while loading."));
+                return;
+            }
+        };
+
+        // Play as the first person at the table, starting from a clean screen.
+        self.player_seat = (0..game.get_player_count()).find(|seat| game.get_seat_kind(*seat) == SeatKind::Human).unwrap_or(0);
+        self.end_data = game.check_ending();
+        self.game = game;
+        self.refresh_legal_moves();
+        self.game_nav_data = GameNavData::new();
+        self.update_items = UpdateItems::new();
+        self.welcome_data.message = None;
+        self.current_screen = CurrentScreen::Playing;
     }
 
     /**
@@ -77,10 +164,10 @@ impl App {
      * Takes in an index for the hand, and plays the given card.
      */
     pub fn play_as_player(&mut self, index: u8) {
-        let error = self.game.get_player_mut_character().play_ui_card(index.into());
+        let error = self.apply_move(GameCommand::PlayCard { hand_index: index.into() });
 
         match error {
-            Some(x) => match x {
+            Err(x) => match x {
                 GameErrors::InvalidActionError(err) => {
                     self.game_nav_data.error_message = Some(err.action_attempted);
                     self.game_nav_data.current_game_section
@@ -97,6 +184,196 @@ impl App {
         }
     }
 
+    /**
+     * undo_move / redo_move
+     * Take back our last move, or make one we took back again.
+     * Moves can only be taken back as far as the last shuffle, draw or decision by someone else; past that, an error says so.
+     */
+    pub fn undo_move(&mut self) {
+        let result = self.game.undo(self.player_seat);
+        self.refresh_legal_moves();
+        self.after_undo(result);
+    }
+
+    pub fn redo_move(&mut self) {
+        let result = self.game.redo(self.player_seat);
+        self.refresh_legal_moves();
+        self.after_undo(result);
+    }
+
+    /**
+     * after_undo
+     * The hand and the decision being made may be different now, so go back to the hand with a clean popup.
+     */
+    fn after_undo(&mut self, result: Result<Vec<GameEvent>, GameErrors>) {
+        match result {
+            Ok(_) => {
+                self.game_nav_data.current_game_section = GameSection::PlayerCards;
+                self.game_nav_data.card_selected = 0;
+                self.end_data = self.game.check_ending();
+            }
+            Err(e) => {
+                self.game_nav_data.prev_game_section = Some(self.game_nav_data.current_game_section.clone());
+                self.handle_error(e);
+                self.game_nav_data.current_game_section = GameSection::ErrorPopup;
+            }
+        }
+    }
+
+    /**
+     * sync_pending_decision
+     * If the game is waiting on us to make a decision, open the decision popup.
+     * Called every frame, as a decision can come up during anyone's turn (an attack, for example).
+     */
+    pub fn sync_pending_decision(&mut self) {
+        let prompt = match self.game.get_pending_decision() {
+            Some(pending) if pending.seat == self.player_seat => pending.request.get_prompt().clone(),
+            _ => {
+                // Nothing to decide, so make sure we aren't left sitting in an empty popup.
+                if self.game_nav_data.current_game_section == GameSection::SelectPopup {
+                    self.game_nav_data.current_game_section = GameSection::PlayerCards;
+                    self.game_nav_data.card_selected = 0;
+                }
+                return
+            }
+        };
+
+        // Don't cover up an error, or re-open a popup that's already open.
+        if self.game_nav_data.current_game_section == GameSection::SelectPopup || self.game_nav_data.current_game_section == GameSection::ErrorPopup {
+            return;
+        }
+
+        self.game_nav_data.current_game_section = GameSection::SelectPopup;
+        self.game_nav_data.selection_message = Some(prompt);
+        self.game_nav_data.decision_cursor = 0;
+        self.game_nav_data.decision_picks.clear();
+    }
+
+    /**
+     * decision_options
+     * The list of options shown in the decision popup, one line each.
+     */
+    pub fn decision_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(pending) = self.game.get_pending_decision() {
+            match &pending.request {
+                DecisionRequest::ChooseCardsFromHand { .. } => {
+                    for card in self.game.get_player_character(self.player_seat).get_hand() {
+                        options.push(card.get_name().clone());
+                    }
+                }
+                DecisionRequest::ChooseCards { cards, .. } | DecisionRequest::OrderCards { cards, .. } => {
+                    options = cards.clone();
+                }
+                DecisionRequest::ChooseSupplyPile { choices, optional, .. } => {
+                    for choice in choices {
+                        options.push(format!("{} (Price: {})", choice.name, choice.cost));
+                    }
+                    if *optional || choices.is_empty() {
+                        options.push(String::from("Take nothing"));
+                    }
+                }
+                DecisionRequest::YesNo { .. } => {
+                    options.push(String::from("Yes"));
+                    options.push(String::from("No"));
+                }
+            }
+        }
+        return options;
+    }
+
+    /**
+     * submit_decision
+     * Answer the pending decision with what's been picked in the popup.
+     * The option under the cursor counts as the pick, for requests that only take one answer.
+     */
+    pub fn submit_decision(&mut self) {
+        let cursor = self.game_nav_data.decision_cursor;
+        let picks = self.game_nav_data.decision_picks.clone();
+
+        let decision = match self.game.get_pending_decision() {
+            Some(pending) => match &pending.request {
+                DecisionRequest::ChooseCardsFromHand { max, .. } | DecisionRequest::ChooseCards { max, .. } => {
+                    if picks.is_empty() && *max == 1 {
+                        Decision::Cards(vec![cursor])
+                    }
+                    else {
+                        Decision::Cards(picks)
+                    }
+                }
+                DecisionRequest::ChooseSupplyPile { choices, .. } => {
+                    if cursor < choices.len() {
+                        Decision::SupplyPile(Some(cursor))
+                    }
+                    else {
+                        Decision::SupplyPile(None)
+                    }
+                }
+                DecisionRequest::YesNo { .. } => Decision::YesNo(cursor == 0),
+                DecisionRequest::OrderCards { .. } => Decision::Order(picks)
+            },
+            None => return
+        };
+
+        match self.apply_move(GameCommand::ResolveDecision(decision)) {
+            Ok(_) => {
+                // Go back to the hand (the cards in it have likely changed), or the buttons if it's empty.
+                self.game_nav_data.selection_message = None;
+                self.game_nav_data.decision_picks.clear();
+                self.game_nav_data.card_selected = 0;
+                if self.game.get_player_character(self.player_seat).get_hand().is_empty() {
+                    self.game_nav_data.current_game_section = GameSection::PlayerButtons;
+                }
+                else {
+                    self.game_nav_data.current_game_section = GameSection::PlayerCards;
+                }
+                self.end_data = self.game.check_ending();
+            }
+            Err(e) => {
+                self.handle_error(e);
+                self.game_nav_data.prev_game_section = Some(GameSection::SelectPopup);
+                self.game_nav_data.current_game_section = GameSection::ErrorPopup;
+            }
+        }
+    }
+
+    /**
+     * open_trash_popup
+     * Show what's in the trash, returning to the current section once any key is pressed.
+     */
+    pub fn open_trash_popup(&mut self) {
+        self.game_nav_data.prev_game_section = Some(self.game_nav_data.current_game_section.clone());
+        self.game_nav_data.current_game_section = GameSection::TrashPopup;
+    }
+
+    /**
+     * kingdom_row_length
+     * How many piles are in a row of the buying area.
+     * Rows are: treasures, victories, then the kingdom split into rows of five.
+     */
+    pub fn kingdom_row_length(&self, row: u8) -> u8 {
+        let data = self.game.get_pile_data();
+        let length = match row {
+            0 => data.treasures.len(),
+            1 => data.victories.len(),
+            _ => data.kingdom_set.len().saturating_sub(5 * (row as usize - 2)).min(5)
+        };
+        return length as u8;
+    }
+
+    /**
+     * selected_pile
+     * The pile under the cursor in the buying area, as an index and the set it's in.
+     */
+    pub fn selected_pile(&self) -> (usize, CardSet) {
+        let column = self.game_nav_data.kingdom_column as usize;
+        match self.game_nav_data.kingdom_row {
+            0 => (column, CardSet::Treasures),
+            1 => (column, CardSet::Victories),
+            row => (column + 5 * (row as usize - 2), CardSet::Kingdoms)
+        }
+    }
+
     /**
      * handle_errror
      * Displays an error screen with the error message if needed.
@@ -128,30 +405,27 @@ impl App {
         match self.game_nav_data.button_selected {
             0 => {
                 // actions/play treasures button
-                match self.game.get_player_character().get_phase() {
+                match self.game.get_player_character(self.player_seat).get_phase() {
                     PlayerPhases::Action => {
-                        self.game.get_player_mut_character().advance_ui_phase();
+                        if let Err(e) = self.apply_move(GameCommand::EndPhase) {
+                            self.handle_error(e);
+                        }
                     },
                     PlayerPhases::Buy => {
-                        self.game.get_player_mut_character().play_all_treasures_ui();
+                        if let Err(e) = self.apply_move(GameCommand::PlayAllTreasures) {
+                            self.handle_error(e);
+                        }
                     },
                     _ => {}
                 }
             },
             1 => {
                 // end turn button
-                self.game.get_player_mut_character().end_turn();
-
-                self.end_data = self.game.check_ending();
-
-                // If the game has ended, stop playing immediately instead of giving the CPU another turn.
-                if self.end_data.is_some() {
-                    return
+                // The game plays every cpu's turn for us, and stops if the game ends on the way around.
+                if let Err(e) = self.apply_move(GameCommand::EndTurn) {
+                    self.handle_error(e);
                 }
 
-                // Play as the cpu.
-                self.game.player2_basic_ai();
-
                 // Check for the ending of the game.
                 self.end_data = self.game.check_ending();
             },
@@ -163,12 +437,23 @@ impl App {
 
 /**
  * Enum representing welcome button.
+ * Play carries on with the game in memory (a new one, when the app starts), Continue loads the saved game,
+ * and Save saves the game in memory.
  */
+#[derive(PartialEq, Clone, Copy)]
 pub enum WelcomeButton {
     Play,
+    Continue,
+    Save,
     Exit
 }
 
+/**
+ * WELCOME_BUTTONS
+ * The buttons on the main menu, top to bottom.
+ */
+pub const WELCOME_BUTTONS: [WelcomeButton; 4] = [WelcomeButton::Play, WelcomeButton::Continue, WelcomeButton::Save, WelcomeButton::Exit];
+
 /**
  * GameSection
  * Which section of the game is currently being manipulated.
@@ -180,7 +465,8 @@ pub enum GameSection {
     Kingdom,
     SelectPopup,
     ErrorPopup,
-    DescriptionPopup
+    DescriptionPopup,
+    TrashPopup
 }
 
 /**
@@ -212,8 +498,14 @@ pub struct GameNavData {
     // The error messages, if there is one.
     pub error_message: Option<String>,
 
-    // Unused. Used for selecting a card to gain/discard/etc.
-    pub selection_message: Option<String>
+    // The prompt of the decision being made in the select popup (selecting a card to gain/discard/etc.)
+    pub selection_message: Option<String>,
+
+    // The option being pointed at in the select popup.
+    pub decision_cursor: usize,
+
+    // The options that have been picked in the select popup, in the order they were picked.
+    pub decision_picks: Vec<usize>
 }
 
 
@@ -232,6 +524,8 @@ impl GameNavData {
             kingdom_column: 0,
             error_message: None,
             selection_message: None,
+            decision_cursor: 0,
+            decision_picks: Vec::new(),
             card_describe: String::from(""),
         }
         
@@ -307,20 +601,22 @@ impl UpdateItems {
  * Welcome screen struct and implementation.
  */
 pub struct WelcomeScreen {
-    pub selected_button: WelcomeButton
+    pub selected_button: WelcomeButton,
+    // How the last save or continue went, shown under the buttons.
+    pub message: Option<String>
 }
 
 impl WelcomeScreen {
     pub fn new() -> WelcomeScreen {
         WelcomeScreen {
-            selected_button: WelcomeButton::Play
+            selected_button: WelcomeButton::Play,
+            message: None
         }
     }
 
-    pub fn toggle_selected_button(&mut self) {
-        match &self.selected_button {
-            WelcomeButton::Play => self.selected_button = WelcomeButton::Exit,
-            WelcomeButton::Exit => self.selected_button = WelcomeButton::Play,
-        };
+    // Move the selection down the buttons by the given amount, wrapping around to the top.
+    pub fn move_selected_button(&mut self, by: usize) {
+        let index = WELCOME_BUTTONS.iter().position(|b| *b == self.selected_button).unwrap_or(0);
+        self.selected_button = WELCOME_BUTTONS[(index + by) % WELCOME_BUTTONS.len()];
     }
 }
\ No newline at end of file
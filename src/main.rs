@@ -27,8 +27,11 @@ use crate::{
     ui::ui,
 };
 
-use dominion_library::game::board::CardSet;
-use dominion_library::player::player::PlayerUIInterface;
+use dominion_library::card_manager::card::Card;
+use dominion_library::card_manager::card_registry::load_card_registry;
+use dominion_library::game::game_command::GameCommand;
+use dominion_library::player::player::PlayerStatsInterface;
+use dominion_library::player::agent::DecisionRequest;
 
 /**
  * Main method
@@ -40,6 +43,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     // So if the program needs to panic, the terminal is still recovered and usable after the panic.
     color_eyre::install()?;
 
+    // Load a different set of cards, if one is given (So cards can be tweaked without recompiling).
+    // This happens before the terminal is taken over, so any problems with the file are printed normally.
+    if let Ok(path) = std::env::var("DOMINION_CARDS") {
+        load_card_registry(&path)?;
+    }
+
     // Enable raw mode so keyboard input can be handled
     enable_raw_mode()?;
     
@@ -93,6 +102,9 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
 
     // Main app loop.
     loop {
+        // If the game is waiting on us to make a decision, bring up the popup for it.
+        app.sync_pending_decision();
+
         // Draw the UI for the terminal
         terminal.draw(|f| ui(f, app))?;
 
@@ -117,6 +129,31 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                     app.current_screen = CurrentScreen::Exiting
                     
                 }
+                // If the player presses escape during a game, go back to the main menu, where the game can be saved.
+                // Play on the menu comes back to it.
+                KeyCode::Esc => {
+                    if let CurrentScreen::Playing = app.current_screen {
+                        app.current_screen = CurrentScreen::Welcome;
+                        continue;
+                    }
+                }
+                // u and r undo and redo our last move, wherever we are in the game (except on top of a popup that any key closes).
+                KeyCode::Char('u') | KeyCode::Char('r') => {
+                    if let CurrentScreen::Playing = app.current_screen {
+                        match app.game_nav_data.current_game_section {
+                            GameSection::PlayerCards | GameSection::Kingdom | GameSection::PlayerButtons | GameSection::SelectPopup => {
+                                if key.code == KeyCode::Char('u') {
+                                    app.undo_move();
+                                }
+                                else {
+                                    app.redo_move();
+                                }
+                                continue;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
                 _ => {}
             }
             // Match the screen we're in.
@@ -124,16 +161,23 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                 // If we're in the welcome screen, use up, down to navigate, and enter to select.
                 CurrentScreen::Welcome => match key.code {
                     KeyCode::Up => {
-                        app.toggle_menu_button();
+                        app.previous_menu_button();
                     }
                     KeyCode::Down => {
-                        app.toggle_menu_button();
+                        app.next_menu_button();
                     }
                     KeyCode::Enter => match app.welcome_data.selected_button {
-                        // Press enter, play the game, or run the exit dialog.
+                        // Press enter, play the game, load or save it, or run the exit dialog.
                         WelcomeButton::Play => {
+                            app.welcome_data.message = None;
                             app.current_screen = CurrentScreen::Playing
                         }
+                        WelcomeButton::Continue => {
+                            app.continue_game();
+                        }
+                        WelcomeButton::Save => {
+                            app.save_game();
+                        }
                         WelcomeButton::Exit => {
                             app.prev_screen = Some(CurrentScreen::Welcome);
                             app.current_screen = CurrentScreen::Exiting
@@ -181,16 +225,20 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                         // In case of error, restore previous screen once the player presses enter.
                         KeyCode::Enter => {
                             app.game_nav_data.restore_before_error();
-                            app.update_items.update_player_stats;
                         }
                         _ => {}
                     },
+                    GameSection::SelectPopup => handle_decision_nav(app, key.code),
                     GameSection::DescriptionPopup => match key.code {
                         _ => {
                             // If we're opening a card description, once a keycode is pressed, go back to the game.
                             app.game_nav_data.current_game_section = app.game_nav_data.prev_game_section.clone().unwrap();
                         }
                     }
+                    GameSection::TrashPopup => {
+                        // Same as the description: any key goes back to the game.
+                        app.game_nav_data.current_game_section = app.game_nav_data.prev_game_section.clone().unwrap();
+                    }
                     _ => {}
 
                 }
@@ -216,7 +264,7 @@ fn handle_playercards_nav(app: &mut App, code:KeyCode) {
         }
         KeyCode::Right => {
             // If pressing right, if we're going to run off the end, go into the playerbutton interface.
-            if app.game.get_player_character().get_hand().len() < (app.game_nav_data.card_selected + 2).into() {
+            if app.game.get_player_character(app.player_seat).get_hand().len() < (app.game_nav_data.card_selected + 2).into() {
                 app.game_nav_data.current_game_section = GameSection::PlayerButtons;
             }
             else {
@@ -228,7 +276,7 @@ fn handle_playercards_nav(app: &mut App, code:KeyCode) {
             // If we're about to run off the rails to the left, go to the player buttons section.
             if app.game_nav_data.card_selected == 0 {
                 app.game_nav_data.current_game_section = GameSection::PlayerButtons;
-                app.game_nav_data.card_selected = app.game.get_player_character().get_hand().len() as u8 - 1;
+                app.game_nav_data.card_selected = (app.game.get_player_character(app.player_seat).get_hand().len() as u8).saturating_sub(1);
             }
             else {
                 // Otherwise, just move left.
@@ -239,7 +287,7 @@ fn handle_playercards_nav(app: &mut App, code:KeyCode) {
 
             // Upon pressing enter, play the card in hand.
             app.play_as_player(app.game_nav_data.card_selected.into());
-            if app.game.get_player_character().get_hand().len() == 0 {
+            if app.game.get_player_character(app.player_seat).get_hand().is_empty() {
                 // If there's no more cards, move to the playerButtons menu.
                 app.game_nav_data.current_game_section = GameSection::PlayerButtons;
             } else {
@@ -257,10 +305,14 @@ fn handle_playercards_nav(app: &mut App, code:KeyCode) {
             // If we press ? in the hand, show a card description.
             app.game_nav_data.prev_game_section = Some(app.game_nav_data.current_game_section.clone());
             app.game_nav_data.current_game_section = GameSection::DescriptionPopup;
-            let transition = app.game.get_player_character().get_hand().get(app.game_nav_data.card_selected as usize).unwrap();
+            let transition = app.game.get_player_character(app.player_seat).get_hand().get(app.game_nav_data.card_selected as usize).unwrap();
             app.game_nav_data.card_describe = transition.get_description().clone();
 
         },
+        KeyCode::Char('t') => {
+            // If t is pressed, show what's in the trash.
+            app.open_trash_popup();
+        },
         _ => {}
     }
 }
@@ -274,67 +326,38 @@ fn handle_kingdom_nav(app: &mut App, code:KeyCode) {
     match code {
         // When we move down,
         KeyCode::Down => {
-            if app.game_nav_data.kingdom_row == 2 {
-                // If we're at the edge of the kingdom rows, move into the player buttons (As we don't know if cards exist)
+            if app.kingdom_row_length(app.game_nav_data.kingdom_row + 1) == 0 {
+                // If there's no row of cards below us, move into the player buttons.
                 app.game_nav_data.current_game_section = GameSection::PlayerButtons;
             } else {
-                // Otherwise, just move down.
+                // Otherwise, just move down, staying inside the new row.
                 app.game_nav_data.kingdom_row += 1;
+                let row_length = app.kingdom_row_length(app.game_nav_data.kingdom_row);
+                if app.game_nav_data.kingdom_column >= row_length {
+                    app.game_nav_data.kingdom_column = row_length - 1;
+                }
             }
             
         },
         KeyCode::Right => {
             // Loop back if we move all the way to the right.
-            if app.game_nav_data.kingdom_row == 0 || app.game_nav_data.kingdom_row == 1 {
-                if app.game_nav_data.kingdom_column == 2 {
-                    app.game_nav_data.kingdom_column = 0;
-                }
-                else {
-                    app.game_nav_data.kingdom_column += 1;
-                }
-            } else if  app.game_nav_data.kingdom_row == 2 {
-                if app.game_nav_data.kingdom_column == 4 {
-                    app.game_nav_data.kingdom_column = 0;
-                }
-                else {
-                    app.game_nav_data.kingdom_column += 1;
-                }
+            let row_length = app.kingdom_row_length(app.game_nav_data.kingdom_row);
+            if app.game_nav_data.kingdom_column + 1 >= row_length {
+                app.game_nav_data.kingdom_column = 0;
             }
             else {
-                if app.game_nav_data.kingdom_column == 4 {
-                    app.game_nav_data.kingdom_column = 0;
-                }
-                else {
-                    app.game_nav_data.kingdom_column += 1;
-                }
+                app.game_nav_data.kingdom_column += 1;
             }
             
         },
         KeyCode::Left => {
             // Loop back if we move all the way to the left.
-            if app.game_nav_data.kingdom_row == 0 || app.game_nav_data.kingdom_row == 1 {
-                if app.game_nav_data.kingdom_column == 0 {
-                    app.game_nav_data.kingdom_column = 2;
-                }
-                else {
-                    app.game_nav_data.kingdom_column -= 1;
-                }
+            let row_length = app.kingdom_row_length(app.game_nav_data.kingdom_row);
+            if app.game_nav_data.kingdom_column == 0 {
+                app.game_nav_data.kingdom_column = row_length.saturating_sub(1);
             }
-            else if  app.game_nav_data.kingdom_row == 2 {
-                if app.game_nav_data.kingdom_column == 0 {
-                    app.game_nav_data.kingdom_column = 4;
-                }
-                else {
-                    app.game_nav_data.kingdom_column -= 1;
-                }
-            } 
             else {
-                if app.game_nav_data.kingdom_column == 0 {
-                    app.game_nav_data.kingdom_column = 4;
-                }
-                else {
-                    app.game_nav_data.kingdom_column -= 1;
-                }
+                app.game_nav_data.kingdom_column -= 1;
             }
             
         },
@@ -342,42 +365,27 @@ fn handle_kingdom_nav(app: &mut App, code:KeyCode) {
             // Move to player buttons if we go up, off the edge.
             if app.game_nav_data.kingdom_row == 0 {
                 app.game_nav_data.current_game_section = GameSection::PlayerButtons;
-            } else if app.game_nav_data.kingdom_row == 2 {
-                app.game_nav_data.kingdom_row -= 1;
-                if app.game_nav_data.kingdom_column > 2 {
-                    app.game_nav_data.kingdom_column = 2;
-                }
             } 
             else {
+                // Otherwise, move up, staying inside the new row.
                 app.game_nav_data.kingdom_row -= 1;
+                let row_length = app.kingdom_row_length(app.game_nav_data.kingdom_row);
+                if app.game_nav_data.kingdom_column >= row_length {
+                    app.game_nav_data.kingdom_column = row_length - 1;
+                }
             }
             
         },
         KeyCode::Enter =>  {
             // On enter, buy a card, and show the error if there is one.
-            let error;
-            match app.game_nav_data.kingdom_row {
-                
-                0 => {
-                    error = app.game.get_player_mut_character().buy_ui_card(app.game_nav_data.kingdom_column.into(), CardSet::Treasures);
-                }
-                1 => {
-                    error = app.game.get_player_mut_character().buy_ui_card(app.game_nav_data.kingdom_column.into(), CardSet::Victories);
-                }
-                _ => {
-                    if app.game_nav_data.kingdom_column > 5 {
-                        error = app.game.get_player_mut_character().buy_ui_card((app.game_nav_data.kingdom_column - 5).into(), CardSet::Kingdoms);
-                    } else {
-                        error = app.game.get_player_mut_character().buy_ui_card((app.game_nav_data.kingdom_column).into(), CardSet::Kingdoms);
-                    }
-                }
-            }
+            let (index, set) = app.selected_pile();
+            let error = app.apply_move(GameCommand::Buy { set: set, index: index });
 
             match error {
-                Some(e) => {
+                Err(e) => {
                     app.handle_error(e);                                    
                 }
-                None => {}
+                Ok(_) => {}
             }
 
             
@@ -389,24 +397,9 @@ fn handle_kingdom_nav(app: &mut App, code:KeyCode) {
             app.game_nav_data.prev_game_section = Some(app.game_nav_data.current_game_section.clone());
             app.game_nav_data.current_game_section = GameSection::DescriptionPopup;
 
-            let error;
-            // Match the column go get the description.
-            match app.game_nav_data.kingdom_row {
-                
-                0 => {
-                    error = app.game.get_pile_desc(app.game_nav_data.kingdom_column.into(), CardSet::Treasures);
-                }
-                1 => {
-                    error = app.game.get_pile_desc(app.game_nav_data.kingdom_column.into(), CardSet::Victories);
-                }
-                _ => {
-                    if app.game_nav_data.kingdom_column > 5 {
-                        error = app.game.get_pile_desc((app.game_nav_data.kingdom_column - 5).into(), CardSet::Kingdoms);
-                    } else {
-                        error = app.game.get_pile_desc((app.game_nav_data.kingdom_column).into(), CardSet::Kingdoms);
-                    }
-                }
-            }
+            // Get the description of the pile under the cursor.
+            let (index, set) = app.selected_pile();
+            let error = app.game.get_pile_desc(index, set);
 
             // If there's an error, handle it.
             match (error) {
@@ -419,12 +412,79 @@ fn handle_kingdom_nav(app: &mut App, code:KeyCode) {
             }
             
 
+        },
+        KeyCode::Char('t') => {
+            // If t is pressed, show what's in the trash.
+            app.open_trash_popup();
         },
         _ => {}
     }
 }
 
 
+/**
+ * handle_decision_nav
+ * Handle the input for the decision popup.
+ * Up and down move between options, space picks (or unpicks) an option, and enter answers.
+ * When putting cards in order, enter places the option under the cursor next, and backspace takes back the last one.
+ */
+fn handle_decision_nav(app: &mut App, code: KeyCode) {
+    let option_count = app.decision_options().len();
+    let ordering = matches!(app.game.get_pending_decision(), Some(pending) if matches!(pending.request, DecisionRequest::OrderCards { .. }));
+
+    match code {
+        KeyCode::Up => {
+            // Loop around to the bottom if we go off the top.
+            if app.game_nav_data.decision_cursor == 0 {
+                app.game_nav_data.decision_cursor = option_count.saturating_sub(1);
+            }
+            else {
+                app.game_nav_data.decision_cursor -= 1;
+            }
+        }
+        KeyCode::Down => {
+            // Loop around to the top if we go off the bottom.
+            if app.game_nav_data.decision_cursor + 1 >= option_count {
+                app.game_nav_data.decision_cursor = 0;
+            }
+            else {
+                app.game_nav_data.decision_cursor += 1;
+            }
+        }
+        KeyCode::Char(' ') => {
+            // Toggle whether the option under the cursor is picked.
+            let cursor = app.game_nav_data.decision_cursor;
+            match app.game_nav_data.decision_picks.iter().position(|x| *x == cursor) {
+                Some(i) => {
+                    app.game_nav_data.decision_picks.remove(i);
+                }
+                None => {
+                    app.game_nav_data.decision_picks.push(cursor);
+                }
+            }
+        }
+        KeyCode::Backspace => {
+            app.game_nav_data.decision_picks.pop();
+        }
+        KeyCode::Enter => {
+            if ordering {
+                // Place the card under the cursor next, and answer once every card has a place.
+                let cursor = app.game_nav_data.decision_cursor;
+                if !app.game_nav_data.decision_picks.contains(&cursor) {
+                    app.game_nav_data.decision_picks.push(cursor);
+                }
+                if app.game_nav_data.decision_picks.len() == option_count {
+                    app.submit_decision();
+                }
+            }
+            else {
+                app.submit_decision();
+            }
+        }
+        _ => {}
+    }
+}
+
 /**
  * Handle the input for the player buttons on the right-hand side.
  */
@@ -432,16 +492,16 @@ fn handle_player_buttons(app: &mut App, code: KeyCode) {
     match code {
         // If moving right, go the farthest-left carrd on the selection area. (loop around)
         KeyCode::Right => {
-            if app.game.get_player_character().get_hand().len() > 0 {
+            if !app.game.get_player_character(app.player_seat).get_hand().is_empty() {
                 app.game_nav_data.current_game_section = GameSection::PlayerCards;
                 app.game_nav_data.card_selected = 0;
             }
         },
         // If moving left, go to the card nearest the buttons.
         KeyCode::Left => {
-            if app.game.get_player_character().get_hand().len() > 0 {
+            if !app.game.get_player_character(app.player_seat).get_hand().is_empty() {
                 app.game_nav_data.current_game_section = GameSection::PlayerCards;
-                app.game_nav_data.card_selected = app.game.get_player_character().get_hand().len() as u8 - 1;
+                app.game_nav_data.card_selected = (app.game.get_player_character(app.player_seat).get_hand().len() as u8).saturating_sub(1);
             }
             
         },
@@ -462,7 +522,10 @@ fn handle_player_buttons(app: &mut App, code: KeyCode) {
         KeyCode::Enter => {
             // Good example of helper methods that should be thoughout this section but aren't.
             app.handle_player_button_press();
-            app.update_items.update_player_stats;
+        }
+        KeyCode::Char('t') => {
+            // If t is pressed, show what's in the trash.
+            app.open_trash_popup();
         }
         _ => {}
     }
@@ -7,12 +7,12 @@ Buckle up.
 */
 
 // Import necessary libraries.
-use dominion_library::{card_manager::card::Card, card_manager::card_properties::CardTypes, player::phases::PlayerPhases, player::player::PlayerUIInterface};
+use dominion_library::{card_manager::card::Card, card_manager::card_registry::CardId, card_manager::card_properties::CardTypes, game::board::CardSet, game::game_command::GameCommand, player::phases::PlayerPhases, player::player::PlayerStatsInterface};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect}, prelude::{Alignment, Stylize}, style::{Color, Modifier, Style}, text::{Line, Span, Text}, widgets::{Block, Borders, Clear, List, ListDirection, Paragraph, Padding, Wrap}, Frame
 };
 
-use crate::app::{App, CurrentScreen, WelcomeButton, GameSection};
+use crate::app::{App, CurrentScreen, WelcomeButton, GameSection, WELCOME_BUTTONS};
 
 // Base ui function:
 // Render welcome, playing, exit, and end screen.
@@ -40,14 +40,18 @@ fn end_screen(frame: &mut Frame, app: &mut App) {
                 .style(Style::default());
 
             
-            // Create the exit game text.
-            let exit_text = vec![
+            // Create the exit game text, with one score line per seat, from first place to last.
+            let mut exit_text = vec![
                 Line::from("The game is over!").style(Style::default().fg(Color::Blue)).alignment(Alignment::Center),
-                Line::from("Score: ").alignment(Alignment::Center),
-                Line::from(format!("{}: VP: {}", data.get(0).unwrap().0, data.get(0).unwrap().1)).alignment(Alignment::Center),
-                Line::from(format!("{}: VP: {}", data.get(1).unwrap().0, data.get(1).unwrap().1)).alignment(Alignment::Center),
-                Line::from(format!("Thank you for playing!")).alignment(Alignment::Center)
+                Line::from("Score: ").alignment(Alignment::Center)
             ];
+            for standing in &data.standings {
+                exit_text.push(Line::from(format!("#{} {}: VP: {} ({} turns)", standing.rank, standing.name, standing.victory_points, standing.turns_taken)).alignment(Alignment::Center));
+            }
+            if data.is_shared_victory() {
+                exit_text.push(Line::from("It's a shared victory!").alignment(Alignment::Center));
+            }
+            exit_text.push(Line::from("Thank you for playing!").alignment(Alignment::Center));
             // Put it in a paragraph widget.
             let exit_paragraph = Paragraph::new(exit_text)
                 .block(exit_popup_block)
@@ -107,15 +111,15 @@ fn playing_screen(frame: &mut Frame, app: &mut App) {
 
         // Render the stats box that shows their buying power, points, and actions.
         if app.update_items.first_render | app.update_items.update_player_stats {
-            render_stats_box(frame, player_stats_area, app.game.get_player_character(), String::from("Player Stats"));
+            render_stats_box(frame, player_stats_area, app.game.get_player_character(app.player_seat), String::from("Player Stats"));
         }
 
         // Render their list of cards.
         if app.update_items.first_render | app.update_items.update_player_cards {
             // let mut state = app.selected_card.borrow_mut();
-            // let hand = CardContainer::get_hand_view(app.game.get_player_character().get_hand());
+            // let hand = CardContainer::get_hand_view(app.game.get_player_character(app.player_seat).get_hand());
             // hand.render(player_play_area, frame.buffer_mut(), state);
-            let card_count: usize = app.game.get_player_character().get_hand().len();
+            let card_count: usize = app.game.get_player_character(app.player_seat).get_hand().len();
             let mut player_area_constraint_vec = vec![];
 
             for _ in 0..card_count {
@@ -125,8 +129,10 @@ fn playing_screen(frame: &mut Frame, app: &mut App) {
             
             let card_layout= Layout::default().direction(Direction::Horizontal).constraints(player_area_constraint_vec).split(player_play_area);
 
+            // Cards that can't be played right now get their names greyed out.
             for i  in 0..card_count {
-                render_card(frame, card_layout[i],  app.game.get_player_character().get_hand().get(i as usize).unwrap(), i == app.game_nav_data.card_selected.into() && app.game_nav_data.current_game_section == GameSection::PlayerCards)
+                let playable = app.legal_moves.contains(&GameCommand::PlayCard { hand_index: i });
+                render_card(frame, card_layout[i],  app.game.get_player_character(app.player_seat).get_hand().get(i).unwrap(), i == usize::from(app.game_nav_data.card_selected) && app.game_nav_data.current_game_section == GameSection::PlayerCards, playable)
             }
         }
 
@@ -154,6 +160,12 @@ fn playing_screen(frame: &mut Frame, app: &mut App) {
         // If the player has requested a card description, render it.
         render_card_description(frame, app);
 
+        // If the player has asked to see the trash, render it.
+        render_trash_popup(frame, app);
+
+        // If the player has a decision to make, render it on top of everything.
+        render_decision_popup(frame, app);
+
 
     }
 }
@@ -187,15 +199,104 @@ fn render_card_description(frame: &mut Frame, app: &mut App) {
     }
 }
 
+/**
+ * render_trash_popup
+ * If the player has asked to see the trash, list everything in it, grouped by card.
+ */
+fn render_trash_popup(frame: &mut Frame, app: &mut App) {
+    if app.game_nav_data.current_game_section == GameSection::TrashPopup {
+        let trash = app.game.get_trash();
+
+        // Count each card, keeping them in the order they were first trashed.
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for name in &trash {
+            match counts.iter_mut().find(|(n, _)| n == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name.clone(), 1))
+            }
+        }
+
+        let mut lines = vec![Line::from(format!("{} cards trashed", trash.len())).style(Style::default().fg(Color::LightYellow)), Line::from("")];
+        for (name, count) in counts {
+            lines.push(Line::from(format!("{} x{}", name, count)));
+        }
+
+        let trash_block = Block::default()
+            .title("Trash")
+            .title_bottom("Press any key to close")
+            .borders(Borders::ALL)
+            .style(Style::default());
+
+        let paragraph = Paragraph::new(lines)
+            .block(trash_block)
+            .wrap(Wrap { trim: false });
+
+        // Center it, clear what's underneath, and render it.
+        let area = centered_rect(40, 50, frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/**
+ * render_decision_popup
+ * If the game is waiting on the player to make a decision, render the options they can pick from.
+ */
+fn render_decision_popup(frame: &mut Frame, app: &mut App) {
+    if app.game_nav_data.current_game_section == GameSection::SelectPopup {
+        let selected_style = Style::default().bg(Color::LightBlue);
+
+        // Create the block with the prompt at the top, and the controls along the bottom.
+        let decision_block = Block::default()
+            .title("Decision")
+            .title_bottom("↑/↓ move - space picks - enter confirms - backspace undoes")
+            .borders(Borders::ALL)
+            .style(Style::default());
+
+        let mut lines = vec![
+            Line::from(app.game_nav_data.selection_message.clone().unwrap_or_default()).style(Style::default().fg(Color::LightYellow)),
+            Line::from("")
+        ];
+
+        // One line per option, marked with its place if it has been picked.
+        for (i, option) in app.decision_options().iter().enumerate() {
+            let marker = match app.game_nav_data.decision_picks.iter().position(|x| *x == i) {
+                Some(place) => format!("[{}]", place + 1),
+                None => String::from("[ ]")
+            };
+            let mut line = Line::from(format!("{} {}", marker, option));
+            if i == app.game_nav_data.decision_cursor {
+                line = line.style(selected_style);
+            }
+            lines.push(line);
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(decision_block)
+            .wrap(Wrap { trim: false });
+
+        // Center it, clear what's underneath, and render it.
+        let area = centered_rect(50, 50, frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
+
 /**
  * Render the buying area
- * Split the buying area into a 4x5 grid, rendering a paragraph in each one.
+ * Split the buying area into a grid five wide, rendering a paragraph in each one.
+ * The treasures and victories get a row each, and the kingdom takes as many rows as it needs.
  * I would've done a card struct, but that's REALLY difficult.
  */
 pub fn render_buying_area(frame: &mut Frame, area: Rect, app: &mut App) {
+    // Get data for the games piles, and which of them the player can buy right now (the rest are greyed out).
+    let data = app.game.get_pile_data();
+    let dimmed_style = Style::default().fg(Color::DarkGray);
+
     // Split the layout vertically.
-    let vert_layout = Layout::vertical([
-        Constraint::Ratio(1, 4); 4
+    let rows = 2 + data.kingdom_set.len().div_ceil(5);
+    let vert_layout = Layout::vertical(vec![
+        Constraint::Ratio(1, rows as u32); rows
     ]).split(area).to_vec();
 
     // Split the layout horizontally.
@@ -207,9 +308,6 @@ pub fn render_buying_area(frame: &mut Frame, area: Rect, app: &mut App) {
     // Change the selected style to the lightblue one we've been using (Yes, there should be one, universal one but limits)
     let selected_style = Style::default().bg(Color::LightBlue);
 
-    // Get data for the games piles.
-    let data = app.game.get_pile_data();
-
     // For each item in the treasure section.
     for i in 0..data.treasures.len() {
         // Put the data in a paragraph, render it in the right box.
@@ -217,6 +315,9 @@ pub fn render_buying_area(frame: &mut Frame, area: Rect, app: &mut App) {
         let mut w = Paragraph::new(vec![Line::from(format!("{}", data.treasures[i].0)),
         Line::from(format!("Left in stock: {}", data.treasures[i].1)),
         Line::from(format!("Price: {}", data.treasures[i].2))]).style(Style::default().fg(Color::LightYellow));
+        if !app.legal_moves.contains(&GameCommand::Buy { set: CardSet::Treasures, index: i }) {
+            w = w.style(dimmed_style);
+        }
         if app.game_nav_data.kingdom_row == 0 && app.game_nav_data.kingdom_column == i as u8 && app.game_nav_data.current_game_section == GameSection::Kingdom {
             w = w.style(selected_style);
         }
@@ -229,6 +330,9 @@ pub fn render_buying_area(frame: &mut Frame, area: Rect, app: &mut App) {
         let mut w = Paragraph::new(vec![Line::from(format!("{}", data.victories[i].0)),
         Line::from(format!("Left in stock: {}", data.victories[i].1)),
         Line::from(format!("Price: {}", data.victories[i].2))]).style(Style::default().fg(Color::LightGreen));
+        if !app.legal_moves.contains(&GameCommand::Buy { set: CardSet::Victories, index: i }) {
+            w = w.style(dimmed_style);
+        }
         if app.game_nav_data.kingdom_row == 1 && app.game_nav_data.kingdom_column == i as u8 && app.game_nav_data.current_game_section == GameSection::Kingdom {
             w = w.style(selected_style);
         }
@@ -241,16 +345,16 @@ pub fn render_buying_area(frame: &mut Frame, area: Rect, app: &mut App) {
         let mut w = Paragraph::new(vec![Line::from(format!("{}", data.kingdom_set[i].0)),
         Line::from(format!("Left in stock: {}", data.kingdom_set[i].1)),
         Line::from(format!("Price: {}", data.kingdom_set[i].2))]).style(Style::default().fg(Color::Gray));
+        if !app.legal_moves.contains(&GameCommand::Buy { set: CardSet::Kingdoms, index: i }) {
+            w = w.style(dimmed_style);
+        }
 
-        if app.game_nav_data.kingdom_row == 2 && app.game_nav_data.kingdom_column == i as u8 && app.game_nav_data.current_game_section == GameSection::Kingdom {
+        // The kingdom fills the rows after the victories, five to a row.
+        let (row, column) = (2 + i / 5, i % 5);
+        if app.game_nav_data.kingdom_row == row as u8 && app.game_nav_data.kingdom_column == column as u8 && app.game_nav_data.current_game_section == GameSection::Kingdom {
             w = w.style(selected_style);
         }
-        if i < 5 {
-            frame.render_widget(w, horizontal_cards[2][i])
-        }
-        else {
-            frame.render_widget(w, horizontal_cards[3][i])
-        }
+        frame.render_widget(w, horizontal_cards[row][column])
         
     }
 
@@ -285,7 +389,9 @@ fn render_player_buttons(frame: &mut Frame, area: Rect, app: &App) {
     // Create the three paragraphs that will act as "Buttons"
     let mut actions_paragraph = Paragraph::new("End Actions").block(button_block.clone()).centered();
     let mut treasures_paragraph = Paragraph::new("Play all treasures").block(button_block.clone()).centered();
-    let mut end_turn_paragraph = Paragraph::new("End Turn").block(button_block.clone()).centered();
+    // The end turn button also shows how many moves can be undone (back to the last shuffle, draw or decision) and redone.
+    let undo_hint = format!("u undo ({}) - r redo ({})", app.game.undo_depth(app.player_seat), app.game.redo_depth(app.player_seat));
+    let mut end_turn_paragraph = Paragraph::new("End Turn").block(button_block.clone().title_bottom(undo_hint)).centered();
 
     // If the window is being used, and the button is selected, change the style accordingly
     if app.game_nav_data.button_selected == 0 && app.game_nav_data.current_game_section == GameSection::PlayerButtons {
@@ -297,11 +403,11 @@ fn render_player_buttons(frame: &mut Frame, area: Rect, app: &App) {
     }
 
     // Depending on the phase, show the "End Actions" and "Play treasures" buttons.
-    if app.game.get_player_character().get_phase() == PlayerPhases::Action {
+    if app.game.get_player_character(app.player_seat).get_phase() == PlayerPhases::Action {
         
         frame.render_widget(actions_paragraph, button_layout[0]);
     }
-    else if app.game.get_player_character().get_phase() == PlayerPhases::Buy {
+    else if app.game.get_player_character(app.player_seat).get_phase() == PlayerPhases::Buy {
         
         frame.render_widget(treasures_paragraph, button_layout[0])
     }
@@ -314,7 +420,7 @@ fn render_player_buttons(frame: &mut Frame, area: Rect, app: &App) {
  * render_stats_box
  * Renders a box of stats that shows information about the user's game.
  */
-fn render_stats_box(frame: &mut Frame, stat_area: Rect, player: &dyn PlayerUIInterface, title: String) {
+fn render_stats_box(frame: &mut Frame, stat_area: Rect, player: &dyn PlayerStatsInterface, title: String) {
     // Create all the strings from player get calls.
     let ac_string = format!("Actions: {}\n", player.get_actions());
     let vp_string = format!("Victory points: {}\n", player.get_victory_points());
@@ -360,9 +466,9 @@ fn render_borders(paragraph: &Paragraph, border: Borders, frame: &mut Frame, are
 
 /**
  * Render_card
- * Renders a card in the player's hand.
+ * Renders a card in the player's hand, with its name greyed out if it can't be played right now.
  */
-fn render_card(frame: &mut Frame, card_area: Rect, card: &Box<dyn Card>, selected: bool) {
+fn render_card(frame: &mut Frame, card_area: Rect, card: &CardId, selected: bool, playable: bool) {
     // Split the given space into a space of exactly 20 in length.
     let card_layout = Layout::horizontal([
         Constraint::Length(20)
@@ -392,7 +498,8 @@ fn render_card(frame: &mut Frame, card_area: Rect, card: &Box<dyn Card>, selecte
 
     // Declare and render card title
     let card_title_block = Block::new().borders(Borders::all()).padding(Padding::top(0));
-    let text = Line::from(Span::styled(card.get_name(), Style::default())).alignment(Alignment::Center);
+    let title_style = if playable { Style::default() } else { Style::default().fg(Color::DarkGray) };
+    let text = Line::from(Span::styled(card.get_name(), title_style)).alignment(Alignment::Center);
     let lp = Paragraph::new(text).block(card_title_block);
     frame.render_widget(lp, card_title);
 
@@ -408,6 +515,9 @@ fn render_card(frame: &mut Frame, card_area: Rect, card: &Box<dyn Card>, selecte
     else if card.get_card_types().contains(&CardTypes::Treasure) {
         bg_color = bg_color.fg(Color::LightYellow);
     }
+    else if card.get_card_types().contains(&CardTypes::Curse) {
+        bg_color = bg_color.fg(Color::Magenta);
+    }
 
 
     // Render Card art (There isn't any at this point.)
@@ -445,6 +555,9 @@ fn render_card(frame: &mut Frame, card_area: Rect, card: &Box<dyn Card>, selecte
             CardTypes::Attack => {
                 card_type_string += "Attack"
             }
+            CardTypes::Curse => {
+                card_type_string += "Curse"
+            }
         }
         iter_first = false;
     }
@@ -466,7 +579,7 @@ fn render_card(frame: &mut Frame, card_area: Rect, card: &Box<dyn Card>, selecte
 fn welcome_screen(frame: &mut Frame, app: &App) {
     if let CurrentScreen::Welcome = app.current_screen {
 
-        // Divide the screen into three segments: the title, and the two buttons
+        // Divide the screen into segments: the title, the buttons, and a line for how saving or loading went.
         let _ = frame.render_widget(Clear, frame.area());
         let title_screen_blocks = Layout::default()
         .margin(3)
@@ -474,6 +587,9 @@ fn welcome_screen(frame: &mut Frame, app: &App) {
             Constraint::Min(5),
             Constraint::Min(3),
             Constraint::Min(3),
+            Constraint::Min(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
         ])
         .split(frame.area());
 
@@ -505,24 +621,29 @@ fn welcome_screen(frame: &mut Frame, app: &App) {
         // Render inside the layout.
         frame.render_widget(title_paragraph, title_screen_blocks[0]);
 
-        // Create the two buttons.
-        let mut play_button = Block::default().borders(Borders::ALL);
-        let mut exit_button = Block::default().borders(Borders::ALL);
-
         let selected_style = Style::default().bg(Color::LightBlue).fg(Color::Black);
 
-        // Add style to them if they are selected.
-        match app.welcome_data.selected_button {
-            WelcomeButton::Play => play_button = play_button.style(selected_style),
-            WelcomeButton::Exit => exit_button = exit_button.style(selected_style),
-        }
+        // Render the buttons with text, adding style to the one that's selected.
+        for (i, button) in WELCOME_BUTTONS.iter().enumerate() {
+            let mut block = Block::default().borders(Borders::ALL);
+            if *button == app.welcome_data.selected_button {
+                block = block.style(selected_style);
+            }
 
-        // Render the two buttons with text.
-        let play_text = Paragraph::new("Play").block(play_button);
-        frame.render_widget(play_text, title_screen_blocks[1]);
+            let text = match button {
+                WelcomeButton::Play => "Play",
+                WelcomeButton::Continue => "Continue saved game",
+                WelcomeButton::Save => "Save game",
+                WelcomeButton::Exit => "Exit"
+            };
+            frame.render_widget(Paragraph::new(text).block(block), title_screen_blocks[i + 1]);
+        }
 
-        let exit_test = Paragraph::new("Exit").block(exit_button);
-        frame.render_widget(exit_test, title_screen_blocks[2]);
+        // Say how the last save or continue went.
+        if let Some(message) = &app.welcome_data.message {
+            let message_line = Line::from(message.clone()).style(Style::default().fg(Color::LightYellow));
+            frame.render_widget(Paragraph::new(message_line), title_screen_blocks[5]);
+        }
     }
 }
 
@@ -543,7 +664,7 @@ fn exit_screen(frame: &mut Frame, app: &App) {
         
         // Create the exit text.
         let exit_text = Text::styled(
-            "Are you sure that you want to exit? If a game is being played, it will not be saved! (y/n)",
+            "Are you sure that you want to exit? If a game is being played, anything since it was last saved will be lost! (Press escape, then Save game on the menu, to save it.) (y/n)",
             Style::default().fg(Color::Red),
         );
 